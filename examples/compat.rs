@@ -1,6 +1,7 @@
-//! Parses `gl.xml` and then prints out Rust source that can bind to it.
+//! Parses `gl.xml` and then prints out Rust source for the parts of GL 4.1
+//! core that GLES 3.0 also has.
 
-use phosphorus::{ApiGroup, GlApiSelection, GlProfile, GlRegistry};
+use phosphorus::{ApiGroup, GlApiSelection, GlProfile, GlRegistry, GlVersion};
 
 fn main() {
   let args: Vec<_> = std::env::args_os().collect();
@@ -24,7 +25,7 @@ fn main() {
   if cfg!(debug_assertions) {
     eprintln!("Reading `{}`", filename.to_str().unwrap());
   }
-  let gl_xml = std::fs::read_to_string(&filename).unwrap();
+  let gl_xml = std::fs::read_to_string(filename).unwrap();
   //
   if cfg!(debug_assertions) {
     eprintln!("Parsing the registry.");
@@ -34,10 +35,10 @@ fn main() {
   if cfg!(debug_assertions) {
     eprintln!("Selecting API_A.");
   }
-  let mut api_a = GlApiSelection::new_from_registry_api_extensions(
+  let api_a = GlApiSelection::new_from_registry_api_extensions(
     &registry,
     ApiGroup::Gl,
-    (4, 1),
+    GlVersion::new(4, 1),
     GlProfile::Core,
     &[],
   );
@@ -45,93 +46,31 @@ fn main() {
   if cfg!(debug_assertions) {
     eprintln!("Selecting API_B.");
   }
-  let mut api_b = GlApiSelection::new_from_registry_api_extensions(
+  let api_b = GlApiSelection::new_from_registry_api_extensions(
     &registry,
     ApiGroup::Gles2,
-    (3, 0),
+    GlVersion::new(3, 0),
     GlProfile::Core,
     &[],
   );
   //
-  let mut out = GlApiSelection::default();
-  //
-  assert_eq!(api_a.gl_types, api_b.gl_types);
-  out.gl_types = api_a.gl_types.clone();
-  //
-  let mut api_a_enum_keys: Vec<_> = api_a.gl_enums.iter().collect();
-  api_a_enum_keys.sort_by_cached_key(|a|a.0.clone());
-  for (k,gl_enum) in api_a_enum_keys {
-    if api_b.gl_enums.contains_key(k) {
-      out.gl_enums.insert(k.clone(), gl_enum.clone());
-    } else {
-      eprintln!("Enum not in {:?} {:?}: {:?}", api_b.api, api_b.version, gl_enum.name)
-    }
-  }
-  //
-  let mut api_a_command_keys: Vec<_> = api_a.gl_commands.iter().collect();
-  api_a_command_keys.sort_by_cached_key(|a|a.0.clone());
-  for (k,gl_command) in api_a_command_keys {
-    if api_b.gl_commands.contains_key(k) {
-      out.gl_commands.insert(k.clone(), gl_command.clone());
-    } else {
-      eprintln!("Command not in {:?} {:?}: {:?}", api_b.api, api_b.version, gl_command.name)
-    }
+  if cfg!(debug_assertions) {
+    eprintln!("Intersecting.");
   }
-  //
-  //
-  //
-  core::mem::swap(&mut api_a, &mut api_b);
-  //
-  //
-  //
-  let mut api_a_enum_keys: Vec<_> = api_a.gl_enums.iter().collect();
-  api_a_enum_keys.sort_by_cached_key(|a|a.0.clone());
-  for (k,gl_enum) in api_a_enum_keys {
-    if api_b.gl_enums.contains_key(k) {
-      out.gl_enums.insert(k.clone(), gl_enum.clone());
-    } else {
-      eprintln!("Enum not in {:?} {:?}: {:?}", api_b.api, api_b.version, gl_enum.name)
+  let out = GlApiSelection::intersection(&api_a, &api_b);
+  if cfg!(debug_assertions) {
+    for name in api_a.gl_enums.keys().filter(|k| !out.gl_enums.contains_key(*k)) {
+      eprintln!("Enum not in {:?} {}: {:?}", api_b.api, api_b.version, name);
     }
-  }
-  //
-  let mut api_a_command_keys: Vec<_> = api_a.gl_commands.iter().collect();
-  api_a_command_keys.sort_by_cached_key(|a|a.0.clone());
-  for (k,gl_command) in api_a_command_keys {
-    if api_b.gl_commands.contains_key(k) {
-      out.gl_commands.insert(k.clone(), gl_command.clone());
-    } else {
-      eprintln!("Command not in {:?} {:?}: {:?}", api_b.api, api_b.version, gl_command.name)
+    for name in
+      api_a.gl_commands.keys().filter(|k| !out.gl_commands.contains_key(*k))
+    {
+      eprintln!("Command not in {:?} {}: {:?}", api_b.api, api_b.version, name);
     }
   }
-  //
-  out.api = ApiGroup::Gl;
-  //
-  out.version = (3, 4);
 
   if cfg!(debug_assertions) {
     eprintln!("Printing.");
   }
-  println!("{}", 0);
+  println!("{}", out);
 }
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
@@ -1,71 +1,991 @@
 //! Parses `gl.xml` and then prints out Rust source that can bind to it.
 
-use phosphorus::{ApiGroup, GlApiSelection, GlProfile, GlRegistry};
+use phosphorus::{
+  ApiGroup, CrateManifest, GlApiSelection, GlProfile, GlRegistry, GlVersion,
+  ParseOptions, RegistryDiff, ReqRem, SelectionDiff,
+};
+use serde::Deserialize;
+use std::{
+  collections::BTreeMap,
+  ffi::OsString,
+  io::{Read, Write},
+  path::{Path, PathBuf},
+  process::exit,
+};
+
+const USAGE: &str = "Usage: phosphorus --registry <gl.xml> --api <api> --version <major.minor> [options]
+       phosphorus --config <file> [--lenient] [--check]
+       phosphorus --registry <gl.xml> --json [--out <path>]
+       phosphorus --registry <gl.xml> --list-features [--api <api>]
+       phosphorus --registry <gl.xml> --list-extensions [--api <api>] [filters]
+       phosphorus query <name> --registry <gl.xml>
+       phosphorus diff <old.xml> [<new.xml>] [--api <api> --version <x.y>]
+
+Options:
+  --registry <path>    The `gl.xml` file to read, or a registry that `--json`
+                       wrote if the name ends in `.json`. Use `-` to read it
+                       from stdin.
+  --api <api>          The API to generate: gl, gles1, gles2, or glsc2.
+  --version <x.y>      The API version to generate, such as `4.6`.
+  --profile <profile>  core (the default) or compatibility.
+  --ext <names>        Extensions to include, comma separated. Can be given
+                       more than once.
+  --out <path>         Write the bindings to this file instead of stdout. The
+                       file is only replaced once the new one is complete.
+  --out-dir <path>     Write the bindings to this folder instead, as a
+                       `lib.rs` plus a file for each module.
+  --cargo-toml <path>  Also write a matching `Cargo.toml` to this path. The
+                       package is named after the folder it's in.
+  --typed-groups       Generate a newtype for each enum group.
+  --extension-modules  Put the items that only one extension adds into a
+                       module named after that extension.
+  --json               Write the selected commands, enums, and groups as JSON
+                       instead of bindings. Without `--api` and `--version`,
+                       write the whole registry as JSON.
+  --check <path>       Don't write anything, just check that the bindings in
+                       this file or folder (and the `--cargo-toml`, if given)
+                       match what would be generated. Prints a diff and fails
+                       if not. A folder also fails if it has any `.rs` files
+                       that wouldn't be generated.
+  --config <path>      Generate every target listed in a config file. With a
+                       config, `--check` takes no path and checks every
+                       target's files instead.
+  --lenient            Skip parts of the registry that aren't understood,
+                       printing a warning for each, instead of failing.
+  -h, --help           Print this help.
+
+Listing:
+  --list-features      List every feature (API version) in the registry, or
+                       just those of `--api`.
+  --list-extensions    List every extension, or just those `--api` supports,
+                       with how many commands and enums each one adds.
+  --vendor <names>     Only list extensions from these vendors, such as
+                       `ARB,EXT`. Can be given more than once.
+  --adds-commands      Only list extensions that add commands.
+  --enums-only         Only list extensions that add enums but no commands.
+
+Querying:
+  query <name>         Explain where a command or enum comes from: the
+                       features that require or remove it, the extensions
+                       that add it, its aliases, and its vector equivalent.
+
+Diffing:
+  diff <old> [<new>]   List the commands, enums, extensions, and features
+                       that one registry adds or removes compared to the
+                       other, and the commands and enums that changed.
+  --api, --version     Compare what these select from each registry instead.
+                       `--profile` and `--ext` also apply. With just one
+                       registry, give `--to-version` as well.
+  --to-version <x.y>   The version to select from the newer registry, if it
+                       isn't the same as `--version`.
+
+Run `phosphorus --version` with no other arguments to print the version of
+phosphorus itself.
+
+Config files are TOML, or JSON if the file name ends in `.json`. Paths in
+them are relative to the config file. Each target is named, and the name is
+also the package name of its `Cargo.toml`:
+
+  registry = \"gl.xml\"
+
+  [targets.gl46]
+  api = \"gl\"
+  version = \"4.6\"
+  profile = \"core\"
+  extensions = [\"GL_EXT_texture_filter_anisotropic\"]
+  out = \"gl46/src/lib.rs\"
+  cargo_toml = \"gl46/Cargo.toml\"
+  typed_groups = true
+
+A target can also set its own `registry`, use `out_dir` instead of `out`,
+and set `extension_modules = true`. The file can set `lenient = true`.";
+
+/// One set of bindings to generate.
+struct Target {
+  registry: PathBuf,
+  api: ApiGroup,
+  version: GlVersion,
+  profile: GlProfile,
+  extensions: Vec<String>,
+  out: Option<PathBuf>,
+  /// The folder to write a file per module to, instead of `out`.
+  out_dir: Option<PathBuf>,
+  cargo_toml: Option<PathBuf>,
+  /// The package name for `cargo_toml`, if it shouldn't be the folder name.
+  crate_name: Option<String>,
+  typed_groups: bool,
+  extension_modules: bool,
+  /// Write the selection as JSON instead of bindings.
+  json: bool,
+}
+
+/// What the command line asked for.
+enum Invocation {
+  Single { target: Target, check: bool },
+  Config { path: PathBuf, check: bool },
+  ListFeatures { registry: PathBuf, api: Option<ApiGroup> },
+  ListExtensions { registry: PathBuf, filter: ExtensionFilter },
+  Query { registry: PathBuf, name: String },
+  RegistryJson { registry: PathBuf, out: Option<PathBuf> },
+  Diff { old: PathBuf, new: PathBuf, selection: Option<DiffSelection> },
+}
+
+/// What `diff` selects from each registry, if it's comparing selections.
+struct DiffSelection {
+  api: ApiGroup,
+  version: GlVersion,
+  to_version: GlVersion,
+  profile: GlProfile,
+  extensions: Vec<String>,
+}
+
+/// Which extensions `--list-extensions` shows.
+struct ExtensionFilter {
+  api: Option<ApiGroup>,
+  vendors: Vec<String>,
+  adds_commands: bool,
+  enums_only: bool,
+}
+
+/// The layout of a config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+  registry: Option<PathBuf>,
+  #[serde(default)]
+  lenient: bool,
+  targets: BTreeMap<String, ConfigTarget>,
+}
+
+/// The layout of one target within a config file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigTarget {
+  registry: Option<PathBuf>,
+  api: String,
+  version: String,
+  profile: Option<String>,
+  #[serde(default)]
+  extensions: Vec<String>,
+  out: Option<PathBuf>,
+  out_dir: Option<PathBuf>,
+  cargo_toml: Option<PathBuf>,
+  #[serde(default)]
+  typed_groups: bool,
+  #[serde(default)]
+  extension_modules: bool,
+}
+
+/// Like `println!`, but see [`write_stdout`].
+macro_rules! outln {
+  ($($arg:tt)*) => {
+    write_stdout(&format!("{}\n", format_args!($($arg)*)))
+  };
+}
+
+/// Writes to stdout. If stdout is a pipe that's been closed, such as by
+/// `head`, this quietly exits instead of panicking like `print!` would.
+fn write_stdout(text: &str) {
+  let mut stdout = std::io::stdout().lock();
+  if let Err(e) =
+    stdout.write_all(text.as_bytes()).and_then(|()| stdout.flush())
+  {
+    if e.kind() == std::io::ErrorKind::BrokenPipe {
+      exit(0);
+    }
+    eprintln!("error: can't write to stdout: {}", e);
+    exit(1);
+  }
+}
 
 fn main() {
-  const USAGE: &str = "Usage: phosphorus <filename> <api> <major> <minor> <profile> [comma,separated,extensions,if,any]";
-  let args: Vec<_> = std::env::args_os().collect();
-  if args.len() == 2 && args[1].to_str() == Some("--version") {
-    println!("phosphorus-{}", env!("CARGO_PKG_VERSION"));
+  let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+  if args.len() == 1 && args[0] == "--version" {
+    outln!("phosphorus-{}", env!("CARGO_PKG_VERSION"));
     return;
   }
-  if args.len() != 6 && args.len() != 7 {
-    panic!(
-      "Illegal Arg Count: Should be either 6 or 7 args, got {count}.\n{usage}",
-      count = args.len(),
-      usage = USAGE
-    );
+  if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+    outln!("{}", USAGE);
+    return;
   }
-  let filename = &args[1];
-  let api = match args[2].to_str().unwrap() {
-    "gl" => ApiGroup::Gl,
-    "gles1" => ApiGroup::Gles1,
-    "gles2" => ApiGroup::Gles2,
-    "glsc2" => ApiGroup::Glsc2,
-    _ => panic!("illegal api name, pick from {{gl,gles1,gles2,glsc2}}"),
+  let parsed = match args.first() {
+    Some(first) if first == "query" => parse_query_args(args),
+    Some(first) if first == "diff" => parse_diff_args(args),
+    _ => parse_args(args),
   };
-  let major: i32 = args[3].to_str().unwrap().parse().unwrap();
-  let minor: i32 = args[4].to_str().unwrap().parse().unwrap();
-  let profile = match args[5].to_str().unwrap() {
-    "core" => GlProfile::Core,
-    "compatibility" => GlProfile::Compatibility,
-    _ => panic!("illegal profile name, pick from {{core,compatibility}}"),
+  let (invocation, lenient) = match parsed {
+    Ok(parsed) => parsed,
+    Err(msg) => {
+      eprintln!("error: {}", msg);
+      eprintln!("Run `phosphorus --help` for usage.");
+      exit(2);
+    }
   };
-  let extensions: Vec<&str> = if args.len() == 7 {
-    args[6]
-      .to_str()
-      .unwrap()
-      .split(',')
-      .map(str::trim)
-      .filter(|s| !s.is_empty())
-      .collect()
-  } else {
-    Vec::new()
+  let result = match invocation {
+    Invocation::Single { target, check } => run(&[target], lenient, check),
+    Invocation::Config { path, check } => {
+      load_config(&path).and_then(|(targets, config_lenient)| {
+        run(&targets, lenient || config_lenient, check)
+      })
+    }
+    Invocation::ListFeatures { registry, api } => {
+      read_registry(&registry, lenient)
+        .map(|registry| list_features(&registry, api))
+    }
+    Invocation::ListExtensions { registry, filter } => {
+      read_registry(&registry, lenient)
+        .map(|registry| list_extensions(&registry, &filter))
+    }
+    Invocation::Query { registry, name } => read_registry(&registry, lenient)
+      .and_then(|registry| {
+        let query = registry
+          .query(&name)
+          .ok_or_else(|| format!("no command or enum named `{}`", name))?;
+        write_stdout(&query.to_string());
+        Ok(())
+      }),
+    Invocation::RegistryJson { registry, out } => {
+      read_registry(&registry, lenient).and_then(|registry| match out {
+        Some(path) => write_file(&path, &registry.to_json()),
+        None => {
+          outln!("{}", registry.to_json());
+          Ok(())
+        }
+      })
+    }
+    Invocation::Diff { old, new, selection } => {
+      diff(&old, &new, selection.as_ref(), lenient)
+    }
+  };
+  if let Err(msg) = result {
+    eprintln!("error: {}", msg);
+    exit(1);
+  }
+}
+
+fn parse_api(name: &str) -> Result<ApiGroup, String> {
+  name.parse().map_err(|_| {
+    format!("unknown api `{}`, pick from gl, gles1, gles2, or glsc2", name)
+  })
+}
+
+fn parse_version(number: &str) -> Result<GlVersion, String> {
+  number.parse().map_err(|_| {
+    format!("can't parse version `{}`, expected something like `4.6`", number)
+  })
+}
+
+fn parse_profile(name: &str) -> Result<GlProfile, String> {
+  match name {
+    "core" => Ok(GlProfile::Core),
+    "compatibility" => Ok(GlProfile::Compatibility),
+    unknown => Err(format!(
+      "unknown profile `{}`, pick from core or compatibility",
+      unknown
+    )),
+  }
+}
+
+/// Walks through the command line arguments, splitting up each `--flag=value`
+/// so that it works the same as `--flag value`.
+struct FlagArgs<I> {
+  args: I,
+  /// The flag that [`next_arg`](Self::next_arg) last gave.
+  flag: String,
+  /// The value that came along with the flag, as in `--flag=value`.
+  inline_value: Option<OsString>,
+}
+impl<I: Iterator<Item = OsString>> FlagArgs<I> {
+  fn new(args: I) -> Self {
+    Self { args, flag: String::new(), inline_value: None }
+  }
+
+  /// The next flag, or the next argument that isn't a flag (such as a path).
+  fn next_arg(&mut self) -> Result<Option<String>, String> {
+    let arg = match self.args.next() {
+      Some(arg) => arg
+        .into_string()
+        .map_err(|arg| format!("argument {:?} isn't valid UTF-8", arg))?,
+      None => return Ok(None),
+    };
+    let (flag, inline_value) = match arg.split_once('=') {
+      Some((flag, value)) if flag.starts_with("--") => {
+        (flag.to_string(), Some(OsString::from(value)))
+      }
+      _ => (arg, None),
+    };
+    self.flag = flag.clone();
+    self.inline_value = inline_value;
+    Ok(Some(flag))
+  }
+
+  /// The value of the last flag.
+  fn value(&mut self) -> Result<OsString, String> {
+    match self.inline_value.take().or_else(|| self.args.next()) {
+      Some(value) => Ok(value),
+      None => Err(format!("`{}` needs a value", self.flag)),
+    }
+  }
+
+  /// The value of the last flag, which has to be text.
+  fn text_value(&mut self) -> Result<String, String> {
+    self
+      .value()?
+      .into_string()
+      .map_err(|value| format!("value {:?} isn't valid UTF-8", value))
+  }
+
+  /// The value of the last flag as a comma separated list.
+  fn list_value(&mut self) -> Result<Vec<String>, String> {
+    Ok(
+      self
+        .text_value()?
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect(),
+    )
+  }
+}
+
+fn parse_args(args: Vec<OsString>) -> Result<(Invocation, bool), String> {
+  let mut registry = None;
+  let mut api = None;
+  let mut version = None;
+  let mut profile = GlProfile::Core;
+  let mut extensions = Vec::new();
+  let mut out = None;
+  let mut out_dir = None;
+  let mut cargo_toml = None;
+  let mut typed_groups = false;
+  let mut extension_modules = false;
+  let mut json = false;
+  let mut config = None;
+  let mut lenient = false;
+  let mut check = false;
+  let mut check_path = None;
+  // with a config file `--check` is just a switch.
+  let config_mode = args.iter().any(|arg| {
+    arg.to_str().map(|a| a == "--config" || a.starts_with("--config="))
+      == Some(true)
+  });
+  let mut list_features = false;
+  let mut list_extensions = false;
+  let mut vendors = Vec::new();
+  let mut adds_commands = false;
+  let mut enums_only = false;
+  // the flags that only make sense for `--list-extensions`.
+  let mut filter_flags = Vec::new();
+  // the flags that only make sense without a config file.
+  let mut target_flags = Vec::new();
+  let mut args = FlagArgs::new(args.into_iter());
+  while let Some(flag) = args.next_arg()? {
+    match flag.as_str() {
+      "--registry" => registry = Some(PathBuf::from(args.value()?)),
+      "--api" => api = Some(parse_api(&args.text_value()?)?),
+      "--version" => version = Some(parse_version(&args.text_value()?)?),
+      "--profile" => profile = parse_profile(&args.text_value()?)?,
+      "--ext" => extensions.extend(args.list_value()?),
+      "--out" => out = Some(PathBuf::from(args.value()?)),
+      "--out-dir" => out_dir = Some(PathBuf::from(args.value()?)),
+      "--cargo-toml" => cargo_toml = Some(PathBuf::from(args.value()?)),
+      "--typed-groups" => typed_groups = true,
+      "--extension-modules" => extension_modules = true,
+      "--json" => json = true,
+      "--config" => config = Some(PathBuf::from(args.value()?)),
+      "--check" if config_mode => check = true,
+      "--check" => {
+        check = true;
+        check_path = Some(PathBuf::from(args.value()?));
+      }
+      "--lenient" => lenient = true,
+      "--list-features" => list_features = true,
+      "--list-extensions" => list_extensions = true,
+      "--vendor" => vendors.extend(args.list_value()?),
+      "--adds-commands" => adds_commands = true,
+      "--enums-only" => enums_only = true,
+      unknown => return Err(format!("unknown argument `{}`", unknown)),
+    }
+    match flag.as_str() {
+      "--config" | "--lenient" | "--list-features" | "--list-extensions" => (),
+      "--check" if config_mode => (),
+      "--vendor" | "--adds-commands" | "--enums-only" => {
+        filter_flags.push(flag)
+      }
+      _ => target_flags.push(flag),
+    }
+  }
+  if let Some(flag) = filter_flags.first().filter(|_| !list_extensions) {
+    return Err(format!("`{}` only works with `--list-extensions`", flag));
+  }
+  if list_features || list_extensions {
+    let mode =
+      if list_features { "--list-features" } else { "--list-extensions" };
+    if list_features && list_extensions {
+      return Err(String::from(
+        "pick one of `--list-features` and `--list-extensions`",
+      ));
+    }
+    if config.is_some() {
+      return Err(format!("`{}` can't be used with `--config`", mode));
+    }
+    if let Some(flag) = target_flags
+      .iter()
+      .find(|flag| !matches!(flag.as_str(), "--registry" | "--api"))
+    {
+      return Err(format!("`{}` can't be used with `{}`", mode, flag));
+    }
+    if adds_commands && enums_only {
+      return Err(String::from(
+        "pick one of `--adds-commands` and `--enums-only`",
+      ));
+    }
+    let registry = registry.ok_or("`--registry` is required")?;
+    let invocation = if list_features {
+      Invocation::ListFeatures { registry, api }
+    } else {
+      let filter = ExtensionFilter { api, vendors, adds_commands, enums_only };
+      Invocation::ListExtensions { registry, filter }
+    };
+    return Ok((invocation, lenient));
+  }
+  if let Some(config) = config {
+    return match target_flags.first() {
+      Some(flag) => Err(format!("`--config` can't be used with `{}`", flag)),
+      None => {
+        let invocation = Invocation::Config { path: config, check };
+        Ok((invocation, lenient))
+      }
+    };
+  }
+  if json && api.is_none() && version.is_none() {
+    if let Some(flag) = target_flags
+      .iter()
+      .find(|flag| !matches!(flag.as_str(), "--registry" | "--json" | "--out"))
+    {
+      return Err(format!(
+        "writing the whole registry as JSON can't use `{}`",
+        flag
+      ));
+    }
+    let registry = registry.ok_or("`--registry` is required")?;
+    return Ok((Invocation::RegistryJson { registry, out }, lenient));
+  }
+  if json && (out_dir.is_some() || cargo_toml.is_some() || extension_modules) {
+    return Err(String::from(
+      "`--json` can't be used with `--out-dir`, `--cargo-toml`, or \
+       `--extension-modules`",
+    ));
+  }
+  if check && out.is_some() {
+    return Err(String::from("`--check` can't be used with `--out`"));
+  }
+  if check && out_dir.is_some() {
+    return Err(String::from("`--check` can't be used with `--out-dir`"));
+  }
+  if out.is_some() && out_dir.is_some() {
+    return Err(String::from("pick one of `--out` and `--out-dir`"));
+  }
+  // a folder given to `--check` holds a file per module.
+  let (out, out_dir) = match check_path {
+    Some(path) if path.is_dir() => (None, Some(path)),
+    Some(path) => (Some(path), None),
+    None => (out, out_dir),
+  };
+  let target = Target {
+    registry: registry.ok_or("`--registry` is required")?,
+    api: api.ok_or("`--api` is required")?,
+    version: version.ok_or("`--version` is required")?,
+    profile,
+    extensions,
+    out,
+    out_dir,
+    cargo_toml,
+    crate_name: None,
+    typed_groups,
+    extension_modules,
+    json,
+  };
+  Ok((Invocation::Single { target, check }, lenient))
+}
+
+/// Parses the arguments of `phosphorus query <name> --registry <gl.xml>`.
+fn parse_query_args(args: Vec<OsString>) -> Result<(Invocation, bool), String> {
+  let mut name = None;
+  let mut registry = None;
+  let mut lenient = false;
+  // skip the `query` itself.
+  let mut args = FlagArgs::new(args.into_iter().skip(1));
+  while let Some(arg) = args.next_arg()? {
+    match arg.as_str() {
+      "--registry" => registry = Some(PathBuf::from(args.value()?)),
+      "--lenient" => lenient = true,
+      flag if flag.starts_with('-') => {
+        return Err(format!("unknown argument `{}` for `query`", flag))
+      }
+      _ if name.is_some() => {
+        return Err(String::from("`query` takes only one name"))
+      }
+      _ => name = Some(arg),
+    }
+  }
+  let invocation = Invocation::Query {
+    registry: registry.ok_or("`--registry` is required")?,
+    name: name.ok_or("`query` needs the name of a command or enum")?,
+  };
+  Ok((invocation, lenient))
+}
+
+/// Parses the arguments of `phosphorus diff <old.xml> [<new.xml>]`.
+fn parse_diff_args(args: Vec<OsString>) -> Result<(Invocation, bool), String> {
+  let mut registries = Vec::new();
+  let mut api = None;
+  let mut version = None;
+  let mut to_version = None;
+  let mut profile = GlProfile::Core;
+  let mut extensions = Vec::new();
+  let mut lenient = false;
+  // skip the `diff` itself.
+  let mut args = FlagArgs::new(args.into_iter().skip(1));
+  while let Some(arg) = args.next_arg()? {
+    match arg.as_str() {
+      "--api" => api = Some(parse_api(&args.text_value()?)?),
+      "--version" => version = Some(parse_version(&args.text_value()?)?),
+      "--to-version" => to_version = Some(parse_version(&args.text_value()?)?),
+      "--profile" => profile = parse_profile(&args.text_value()?)?,
+      "--ext" => extensions.extend(args.list_value()?),
+      "--lenient" => lenient = true,
+      flag if flag.starts_with('-') && flag != "-" => {
+        return Err(format!("unknown argument `{}` for `diff`", flag))
+      }
+      _ if registries.len() == 2 => {
+        return Err(String::from("`diff` takes at most two registries"))
+      }
+      _ => registries.push(PathBuf::from(arg)),
+    }
+  }
+  let old = registries.first().cloned().ok_or("`diff` needs a registry")?;
+  let new = registries.get(1).cloned();
+  let selection = match (api, version) {
+    (Some(api), Some(version)) => {
+      if new.is_none() && to_version.is_none() {
+        return Err(String::from(
+          "with just one registry, `diff` needs `--to-version`",
+        ));
+      }
+      Some(DiffSelection {
+        api,
+        version,
+        to_version: to_version.unwrap_or(version),
+        profile,
+        extensions,
+      })
+    }
+    (None, None) => {
+      if to_version.is_some() || !extensions.is_empty() {
+        return Err(String::from(
+          "`--to-version` and `--ext` need `--api` and `--version`",
+        ));
+      }
+      if new.is_none() {
+        return Err(String::from(
+          "`diff` needs two registries, or `--api` and `--version`",
+        ));
+      }
+      None
+    }
+    _ => {
+      return Err(String::from("`--api` and `--version` go together"));
+    }
   };
+  let new = new.unwrap_or_else(|| old.clone());
+  Ok((Invocation::Diff { old, new, selection }, lenient))
+}
 
+/// Reads a config file into its list of targets, and if it asks for lenient
+/// parsing.
+fn load_config(path: &Path) -> Result<(Vec<Target>, bool), String> {
+  let text = std::fs::read_to_string(path)
+    .map_err(|e| format!("can't read `{}`: {}", path.display(), e))?;
+  let is_json = path.extension().map(|ext| ext == "json").unwrap_or(false);
+  let config: ConfigFile = if is_json {
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+  } else {
+    toml::from_str(&text).map_err(|e| e.to_string())
+  }
+  .map_err(|msg| format!("`{}`: {}", path.display(), msg))?;
+  let base = path.parent().unwrap_or_else(|| Path::new(""));
+  let mut targets = Vec::new();
+  let default_registry = config.registry;
+  for (name, target) in config.targets {
+    let in_target = |msg: String| format!("target `{}`: {}", name, msg);
+    let registry = target
+      .registry
+      .or_else(|| default_registry.clone())
+      .ok_or_else(|| in_target(String::from("no `registry` is set")))?;
+    let (out, out_dir) = match (target.out, target.out_dir) {
+      (Some(out), None) => (Some(base.join(out)), None),
+      (None, Some(out_dir)) => (None, Some(base.join(out_dir))),
+      _ => {
+        return Err(in_target(String::from(
+          "set exactly one of `out` and `out_dir`",
+        )))
+      }
+    };
+    targets.push(Target {
+      registry: if registry == Path::new("-") {
+        registry
+      } else {
+        base.join(registry)
+      },
+      api: parse_api(&target.api).map_err(in_target)?,
+      version: parse_version(&target.version).map_err(in_target)?,
+      profile: match target.profile {
+        Some(profile) => parse_profile(&profile).map_err(in_target)?,
+        None => GlProfile::Core,
+      },
+      extensions: target.extensions,
+      out,
+      out_dir,
+      cargo_toml: target.cargo_toml.map(|path| base.join(path)),
+      crate_name: Some(name),
+      typed_groups: target.typed_groups,
+      extension_modules: target.extension_modules,
+      json: false,
+    });
+  }
+  Ok((targets, config.lenient))
+}
+
+/// Generates every target, parsing each registry file only once.
+///
+/// When checking, nothing is written and it's an error if any file is out of
+/// date.
+fn run(targets: &[Target], lenient: bool, check: bool) -> Result<(), String> {
+  let mut registries: BTreeMap<&Path, GlRegistry> = BTreeMap::new();
+  let mut out_of_date = 0;
+  for target in targets.iter() {
+    if !registries.contains_key(target.registry.as_path()) {
+      let registry = read_registry(&target.registry, lenient)?;
+      registries.insert(&target.registry, registry);
+    }
+    let outputs = generate(&registries[target.registry.as_path()], target)?;
+    if let (true, Some(out_dir)) = (check, target.out_dir.as_ref()) {
+      for path in rust_files_in(out_dir)? {
+        if !outputs.iter().any(|(p, _)| p.as_deref() == Some(path.as_path())) {
+          eprintln!("`{}` wouldn't be generated", path.display());
+          out_of_date += 1;
+        }
+      }
+    }
+    for (path, contents) in outputs {
+      match path {
+        Some(path) if check => {
+          if !check_file(&path, &contents)? {
+            out_of_date += 1;
+          }
+        }
+        Some(path) => write_file(&path, &contents)?,
+        None => outln!("{}", contents),
+      }
+    }
+  }
+  match out_of_date {
+    0 => Ok(()),
+    1 => Err(String::from("1 file is out of date")),
+    n => Err(format!("{} files are out of date", n)),
+  }
+}
+
+fn read_registry(path: &Path, lenient: bool) -> Result<GlRegistry, String> {
   if cfg!(debug_assertions) {
-    eprintln!("Reading `{}`", filename.to_str().unwrap());
+    eprintln!("Reading `{}`", path.display());
+  }
+  let from_stdin = path == Path::new("-");
+  let source = if from_stdin {
+    String::from("stdin")
+  } else {
+    format!("`{}`", path.display())
+  };
+  let gl_xml = if from_stdin {
+    let mut text = String::new();
+    std::io::stdin()
+      .read_to_string(&mut text)
+      .map(|_| text)
+      .map_err(|e| format!("can't read stdin: {}", e))?
+  } else {
+    std::fs::read_to_string(path)
+      .map_err(|e| format!("can't read {}: {}", source, e))?
+  };
+  // stdin has no file name, but XML can't start with `{`.
+  let is_json = if from_stdin {
+    gl_xml.trim_start().starts_with('{')
+  } else {
+    path.extension().map(|ext| ext == "json").unwrap_or(false)
+  };
+  if is_json {
+    return GlRegistry::from_json(&gl_xml)
+      .map_err(|e| format!("{}: {}", source, e));
   }
-  let gl_xml = std::fs::read_to_string(&filename).unwrap();
 
   if cfg!(debug_assertions) {
     eprintln!("Parsing the registry.");
   }
-  let registry = GlRegistry::from_gl_xml_str(&gl_xml);
+  let options = ParseOptions { strict: !lenient };
+  let (registry, warnings) =
+    GlRegistry::try_from_gl_xml_str_with_options(&gl_xml, options)
+      .map_err(|e| format!("{}: {}", source, e))?;
+  for warning in warnings.iter() {
+    eprintln!("warning: {}", warning);
+  }
+  Ok(registry)
+}
+
+/// Prints what changed from the `old` registry to the `new` one, or between
+/// what's selected from each.
+fn diff(
+  old: &Path, new: &Path, selection: Option<&DiffSelection>, lenient: bool,
+) -> Result<(), String> {
+  let old_registry = read_registry(old, lenient)?;
+  let new_registry = if new == old {
+    old_registry.clone()
+  } else {
+    read_registry(new, lenient)?
+  };
+  match selection {
+    Some(selection) => {
+      let extensions: Vec<&str> =
+        selection.extensions.iter().map(String::as_str).collect();
+      let select = |path: &Path, registry: &GlRegistry, version: GlVersion| {
+        GlApiSelection::try_new_from_registry_api_extensions(
+          registry,
+          selection.api,
+          version,
+          selection.profile,
+          &extensions,
+        )
+        .map_err(|msg| format!("`{}`: {}", path.display(), msg))
+      };
+      write_stdout(
+        &SelectionDiff::between(
+          &select(old, &old_registry, selection.version)?,
+          &select(new, &new_registry, selection.to_version)?,
+        )
+        .to_string(),
+      );
+    }
+    None => write_stdout(
+      &RegistryDiff::between(&old_registry, &new_registry).to_string(),
+    ),
+  }
+  Ok(())
+}
 
+/// Makes the files for a target, as `(path, contents)` pairs. A file without
+/// a path goes to stdout.
+fn generate(
+  registry: &GlRegistry, target: &Target,
+) -> Result<Vec<(Option<PathBuf>, String)>, String> {
   if cfg!(debug_assertions) {
     eprintln!("Selecting the correct API.");
   }
-  let selection = GlApiSelection::new_from_registry_api_extensions(
-    &registry,
-    api,
-    (major, minor),
-    profile,
+  let extensions: Vec<&str> =
+    target.extensions.iter().map(String::as_str).collect();
+  let mut selection = GlApiSelection::try_new_from_registry_api_extensions(
+    registry,
+    target.api,
+    target.version,
+    target.profile,
     &extensions,
-  );
+  )?;
+  selection.generator.typed_enum_groups = target.typed_groups;
+  selection.generator.extension_modules = target.extension_modules;
+
+  let mut outputs = Vec::new();
+  if let Some(path) = target.cargo_toml.as_ref() {
+    let crate_name = match target.crate_name.as_ref() {
+      Some(name) => name.clone(),
+      None => folder_name(path),
+    };
+    let manifest = CrateManifest::for_selection(&selection, &crate_name);
+    outputs.push((Some(path.clone()), manifest.to_string()));
+  }
 
   if cfg!(debug_assertions) {
     eprintln!("Printing.");
   }
-  println!("{}", selection);
+  match target.out_dir.as_ref() {
+    None if target.json => {
+      outputs.push((target.out.clone(), selection.to_json()))
+    }
+    Some(out_dir) => {
+      for (path, contents) in selection.module_files() {
+        outputs.push((Some(out_dir.join(path)), contents));
+      }
+    }
+    None => outputs.push((target.out.clone(), selection.to_string())),
+  }
+  Ok(outputs)
+}
+
+/// The name of the folder a file is in, which is what `cargo init` names a
+/// package.
+fn folder_name(path: &Path) -> String {
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+  // the folder might not have been made yet.
+  std::fs::canonicalize(dir)
+    .unwrap_or_else(|_| dir.to_path_buf())
+    .file_name()
+    .and_then(|name| name.to_str())
+    .map(String::from)
+    .unwrap_or_else(|| String::from("gl"))
+}
+
+/// Compares a file on disk with what it should contain, printing a unified
+/// diff if they're different. Gives if the file is up to date.
+///
+/// Extra newlines at the end don't count, since output that was redirected
+/// from stdout has one.
+/// Lists the `.rs` files in `dir` and every folder within it, which is
+/// nothing at all if `dir` doesn't exist.
+fn rust_files_in(dir: &Path) -> Result<Vec<PathBuf>, String> {
+  let mut files = Vec::new();
+  let mut dirs = vec![dir.to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    let entries = match std::fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+      Err(e) => return Err(format!("can't read `{}`: {}", dir.display(), e)),
+    };
+    for entry in entries {
+      let path = entry
+        .map_err(|e| format!("can't read `{}`: {}", dir.display(), e))?
+        .path();
+      if path.is_dir() {
+        dirs.push(path);
+      } else if path.extension().map(|ext| ext == "rs").unwrap_or(false) {
+        files.push(path);
+      }
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+fn check_file(path: &Path, expected: &str) -> Result<bool, String> {
+  /// How much of a diff to print before cutting it off.
+  const MAX_DIFF_LINES: usize = 100;
+  let found = match std::fs::read_to_string(path) {
+    Ok(found) => found,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+    Err(e) => return Err(format!("can't read `{}`: {}", path.display(), e)),
+  };
+  let found = format!("{}\n", found.trim_end_matches('\n'));
+  let expected = format!("{}\n", expected.trim_end_matches('\n'));
+  if found == expected {
+    return Ok(true);
+  }
+  let diff = similar::TextDiff::configure()
+    .timeout(std::time::Duration::from_secs(5))
+    .diff_lines(&found, &expected);
+  let unified = diff
+    .unified_diff()
+    .header(
+      &format!("{} (on disk)", path.display()),
+      &format!("{} (generated)", path.display()),
+    )
+    .to_string();
+  let line_count = unified.lines().count();
+  for line in unified.lines().take(MAX_DIFF_LINES) {
+    outln!("{}", line);
+  }
+  if line_count > MAX_DIFF_LINES {
+    outln!("... and {} more lines of diff", line_count - MAX_DIFF_LINES);
+  }
+  eprintln!("`{}` is out of date", path.display());
+  Ok(false)
+}
+
+/// Writes a file by way of a temporary file in the same folder that's renamed
+/// over it at the end, so a failed run never leaves half a file behind.
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+  if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+    std::fs::create_dir_all(dir)
+      .map_err(|e| format!("can't create `{}`: {}", dir.display(), e))?;
+  }
+  let file_name = path
+    .file_name()
+    .ok_or_else(|| format!("`{}` isn't a file name", path.display()))?;
+  let mut temp_name = OsString::from(".");
+  temp_name.push(file_name);
+  temp_name.push(format!(".{}.tmp", std::process::id()));
+  let temp_path = path.with_file_name(temp_name);
+  let write_temp = || -> std::io::Result<()> {
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+  };
+  write_temp().map_err(|e| {
+    std::fs::remove_file(&temp_path).ok();
+    format!("can't write `{}`: {}", path.display(), e)
+  })
+}
+
+/// Prints each feature as its API, version, and name.
+fn list_features(registry: &GlRegistry, api: Option<ApiGroup>) {
+  let mut features: Vec<_> = registry
+    .gl_features
+    .iter()
+    .filter(|gl_feature| api.map(|a| a == gl_feature.api).unwrap_or(true))
+    .collect();
+  features
+    .sort_by_key(|gl_feature| (gl_feature.api.supported(), gl_feature.number));
+  for gl_feature in features {
+    outln!(
+      "{:<6} {:<5} {}",
+      gl_feature.api.supported(),
+      gl_feature.number.to_string(),
+      gl_feature.name
+    );
+  }
+}
+
+/// Prints each extension that passes the filter, with how many commands and
+/// enums it adds.
+fn list_extensions(registry: &GlRegistry, filter: &ExtensionFilter) {
+  for gl_extension in registry.gl_extensions.iter() {
+    if let Some(api) = filter.api {
+      if !gl_extension.is_supported_by(api) {
+        continue;
+      }
+    }
+    if !filter.vendors.is_empty()
+      && !filter.vendors.iter().any(|v| v == gl_extension.vendor())
+    {
+      continue;
+    }
+    let (mut commands, mut enums) = (0, 0);
+    let mut count = |adjustment: &ReqRem| match adjustment {
+      ReqRem::Command(_) => commands += 1,
+      ReqRem::Enum(_) => enums += 1,
+      ReqRem::Type(_) => (),
+    };
+    match filter.api {
+      Some(api) => gl_extension.requirements_for(api).for_each(&mut count),
+      None => {
+        gl_extension.required.iter().for_each(|req| count(&req.adjustment))
+      }
+    }
+    if (filter.adds_commands && commands == 0)
+      || (filter.enums_only && (commands != 0 || enums == 0))
+    {
+      continue;
+    }
+    outln!("{:<48} {} commands, {} enums", gl_extension.name, commands, enums);
+  }
 }
@@ -10,7 +10,7 @@
 //! let selection = GlApiSelection::new_from_registry_api_extensions(
 //!   &registry,
 //!   ApiGroup::Gl,
-//!   (4, 6),
+//!   GlVersion::new(4, 6),
 //!   GlProfile::Core,
 //!   &["GL_EXT_texture_filter_anisotropic"],
 //! );
@@ -18,12 +18,19 @@
 //! ```
 //!
 //! If you `cargo install phosphorus` you'll get a binary that will do
-//! approximately this if you give it some CLI args.
+//! approximately this if you give it some CLI args (see `phosphorus --help`).
+//! The binary needs the default `cli` feature. If you only use this as a
+//! library you can turn off default features to skip the binary's
+//! dependencies.
 //!
-//! Note that the generated output is very ugly (run `rustfmt` on it) and also
-//! relatively large for a single source file (~2mb). The output is primarily
-//! intended to be put as its own crate data, though you could easily edit a few
-//! parts and have it work as a single module within a larger crate if you like.
+//! The generated output is already indented and wrapped, so there's no need to
+//! run `rustfmt` on it, but it's relatively large for a single source file
+//! (~2mb). The output is primarily intended to be put as its own crate data,
+//! though you could easily edit a few parts and have it work as a single module
+//! within a larger crate if you like.
+//!
+//! With the `json` feature the registry and selections can also be saved as
+//! JSON, for tools that aren't written in Rust (see `GlRegistry::to_json`).
 //!
 //! You are **highly encouraged** to not run this as part of your `build.rs`
 //! process. You should run this once, put the output into your project or
@@ -34,12 +41,14 @@
 use magnesium::{XmlElement::*, *};
 
 use std::{
-  collections::{HashMap, HashSet},
+  collections::{BTreeMap, BTreeSet, HashMap},
   fmt::Write,
 };
 
 /// The two GL profile styles.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum GlProfile {
   /// Core profile, removes deprecated items.
   Core,
@@ -47,6 +56,46 @@ pub enum GlProfile {
   Compatibility,
 }
 
+/// A version number of a GL API, such as `4.6`.
+///
+/// Versions are ordered by number, so `4.10` comes after `4.6`.
+/// ```rust
+/// # use phosphorus::*;
+/// let v4_6: GlVersion = "4.6".parse().unwrap();
+/// let v4_10: GlVersion = "4.10".parse().unwrap();
+/// assert!(v4_6 < v4_10);
+/// assert_eq!(v4_10, GlVersion::new(4, 10));
+/// assert_eq!(v4_10.to_string(), "4.10");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlVersion {
+  /// The major version, the `4` of `4.6`.
+  pub major: u32,
+  /// The minor version, the `6` of `4.6`.
+  pub minor: u32,
+}
+impl GlVersion {
+  /// Makes a version from its parts.
+  pub const fn new(major: u32, minor: u32) -> Self {
+    Self { major, minor }
+  }
+}
+impl core::fmt::Display for GlVersion {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{}.{}", self.major, self.minor)
+  }
+}
+impl core::str::FromStr for GlVersion {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let parts = s.split_once('.').and_then(|(major, minor)| {
+      Some(Self::new(major.parse().ok()?, minor.parse().ok()?))
+    });
+    parts.ok_or_else(|| format!("illegal version number: {}", s))
+  }
+}
+
 /// Does `writeln!(args)?`
 macro_rules! show {
   ($dst:expr) => { writeln!($dst)? };
@@ -68,6 +117,9 @@ const STANDARD_DOCS: &str = r#"//!
 //!   global functions.
 //! * `struct_loader`: Include all mechanisms necessary for calling GL as
 //!   methods on a struct.
+//! * `lazy_loader`: Along with `global_loader`, adds `set_loader`, which lets
+//!   each function load itself the first time that it's called instead of
+//!   all of them being loaded up front.
 //! * `debug_trace_calls`: if cfg!(debug_assertions), any call to a GL function
 //!   will `trace!` what was called and with what args.
 //! * `debug_automatic_glGetError`: If cfg!(debug_assertions), this will
@@ -129,6 +181,22 @@ const STANDARD_DOCS: &str = r#"//!
 //! All of the "`_is_loaded`" functions are hidden in the generated docs just
 //! to keep things tidy, but they're there.
 //!
+//! Both loaders also give back a [`LoadReport`] (use
+//! [`GlFns::load_with_report`] for the struct loader), which lists every
+//! function that came back missing (a null pointer) or rejected (an obviously
+//! bogus address such as `-1`). Logging the report at startup is a cheap way
+//! to find out early that the driver doesn't provide what you target.
+//!
+//! When a function's own name doesn't load, the loaders try the names of its
+//! aliases from the registry, such as `glBindBufferARB` for `glBindBuffer`.
+//! The report's `aliased` list says which functions got loaded that way, and
+//! by which name.
+//!
+//! There are also loaders for just the functions that one extension or
+//! version adds, such as `load_version_3_3_with`, which give if all of them
+//! loaded. They're an easy way to check for the features you need, one tier
+//! at a time.
+//!
 //! # Safety
 //! In general, there's many ways that GL can go wrong.
 //!
@@ -145,25 +213,171 @@ const STANDARD_DOCS: &str = r#"//!
 /// A particular selection of GL items.
 ///
 /// Once you make one of these you can just use the `Display` impl to make it
-/// print out the appropriate bindings.
+/// print out the appropriate bindings. They come out already laid out, with
+/// two spaces per level of indentation and one item per line.
 /// ```rust
 /// # use phosphorus::*;
-/// println!("{}", GlApiSelection::default());
+/// let bindings = GlApiSelection::default().to_string();
+/// println!("{}", bindings);
+/// assert!(bindings.contains("\npub mod types {\n  //! Contains all the GL types.\n"));
+/// assert!(bindings.lines().all(|line| line == line.trim_end()));
 /// ```
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct GlApiSelection {
   pub gl_types: Vec<GlType>,
-  pub gl_enums: HashMap<String, GlEnum>,
-  pub gl_commands: HashMap<String, GlCommand>,
+  pub gl_enums: BTreeMap<String, GlEnum>,
+  pub gl_commands: BTreeMap<String, GlCommand>,
+  /// The enum groups, with each group's list narrowed down to only the enums
+  /// that are in this selection.
+  pub gl_groups: BTreeMap<String, GlGroup>,
   pub api: ApiGroup,
-  pub version: (i32, i32),
+  pub version: GlVersion,
   pub extensions: Vec<String>,
+  /// The commands that each of the `extensions` requires, including any that
+  /// the core API has too.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub extension_commands: BTreeMap<String, Vec<String>>,
+  /// The commands that each version of the API adds, from the first version
+  /// up to `version`.
+  ///
+  /// This is empty in a selection made by combining other selections, since
+  /// each API counts its versions differently.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub version_commands: Vec<(GlVersion, Vec<String>)>,
+  /// Options for the style of the generated bindings.
+  pub generator: GeneratorOptions,
+  /// Every API that went into this selection, when it was made by combining
+  /// other selections with [`intersection`](Self::intersection) or
+  /// [`union`](Self::union). Empty otherwise.
+  pub combined_apis: Vec<(ApiGroup, GlVersion)>,
+}
+
+/// Options that change the style of the bindings that a [`GlApiSelection`]
+/// generates.
+///
+/// The default is plain bindings that look a lot like the C API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneratorOptions {
+  /// Generate a `#[repr(transparent)]` newtype for each enum group that a
+  /// command parameter uses, with the group's enums as associated constants.
+  ///
+  /// Commands then take the newtype instead of a plain `GLenum` (or
+  /// `GLbitfield`) for those parameters. The newtype's field is public, so
+  /// you can still wrap any raw value that you need to pass.
+  ///
+  /// Groups used as a `GLbitfield` are bit flags, so their types also get the
+  /// bitwise operators, `empty`, `all`, `contains`, and a `Debug` impl that
+  /// shows the flag names.
+  pub typed_enum_groups: bool,
+  /// Put the items that only one extension adds into a private submodule
+  /// named after that extension, within each of the `enums`,
+  /// `global_commands`, and `struct_commands` modules.
+  ///
+  /// The items are still re-exported from their usual module and the top of
+  /// the crate, so this only changes how the source is organized. It's mostly
+  /// useful along with [`GlApiSelection::module_files`], to keep each file
+  /// smaller.
+  pub extension_modules: bool,
 }
+/// Prints the bindings as one source file.
+///
+/// Lines that would be too wide have their longest list split up, one
+/// element per line, but only where a trailing comma after the last element
+/// doesn't change what the code means.
+/// ```rust
+/// # use phosphorus::*;
+/// let registry = GlRegistry::from_gl_xml_str(concat!(
+///   "<registry><enums namespace=\"GL\">",
+///   "<enum value=\"(0x8000 + 0x1)\" ",
+///   "name=\"GL_MAX_COMBINED_TESS_EVALUATION_UNIFORM_COMPONENTS\"/>",
+///   "</enums><commands namespace=\"GL\">",
+///   "<command><proto>void ",
+///   "<name>glDrawElementsInstancedBaseVertexBaseInstance</name>",
+///   "</proto></command><command><proto>void ",
+///   "<name>glDrawElementsInstancedBaseVertexBaseInstanceEXT</name></proto>",
+///   "<alias name=\"glDrawElementsInstancedBaseVertexBaseInstance\"/>",
+///   "</command></commands>",
+///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\"><require>",
+///   "<enum name=\"GL_MAX_COMBINED_TESS_EVALUATION_UNIFORM_COMPONENTS\"/>",
+///   "<command name=\"glDrawElementsInstancedBaseVertexBaseInstance\"/>",
+///   "<command name=\"glDrawElementsInstancedBaseVertexBaseInstanceEXT\"/>",
+///   "</require></feature></registry>",
+/// ));
+/// let selection = GlApiSelection::new_from_registry_api_extensions(
+///   &registry,
+///   ApiGroup::Gl,
+///   GlVersion::new(1, 0),
+///   GlProfile::Core,
+///   &[],
+/// );
+/// let bindings = selection.to_string();
+///
+/// // `pub(crate)` and `(a + b)` can't take a trailing comma, so they stay put.
+/// assert!(bindings.contains(concat!(
+///   "\n  pub(crate) static ",
+///   "glDrawElementsInstancedBaseVertexBaseInstanceEXT_p: APcv = ap_null();\n"
+/// )));
+/// assert!(bindings.contains(concat!(
+///   "\n  pub const GL_MAX_COMBINED_TESS_EVALUATION_UNIFORM_COMPONENTS: ",
+///   "GLenum = (0x8000 + 0x1);\n"
+/// )));
+///
+/// // the names that a command can be loaded by go one per line.
+/// assert!(bindings.contains(concat!(
+///   "&[\n",
+///   "            b\"glDrawElementsInstancedBaseVertexBaseInstanceEXT\\0\",\n",
+///   "            b\"glDrawElementsInstancedBaseVertexBaseInstance\\0\",\n",
+///   "          ],\n",
+/// )));
+/// ```
 impl core::fmt::Display for GlApiSelection {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let mut unformatted = String::new();
+    self.bindings_module()?.write_inline(&mut unformatted)?;
+    f.write_str(&layout_rust_source(&unformatted))
+  }
+}
+impl GlApiSelection {
+  /// Gives the bindings split up into a file per module, as `(path, contents)`
+  /// pairs.
+  ///
+  /// The paths are relative to the crate's `src` folder, starting with
+  /// `lib.rs`, then `types.rs`, `enums.rs`, `global_commands.rs`, and
+  /// `struct_commands.rs` (and `groups.rs` with typed enum groups). With
+  /// [`extension_modules`](GeneratorOptions::extension_modules) each
+  /// extension's module goes in a folder named after its parent module, such
+  /// as `enums/GL_KHR_debug.rs`. All together they're the same code that the
+  /// `Display` impl prints as a single file.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let files = GlApiSelection::default().module_files();
+  /// let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+  /// assert_eq!(
+  ///   paths,
+  ///   ["lib.rs", "types.rs", "enums.rs", "global_commands.rs", "struct_commands.rs"]
+  /// );
+  /// assert!(files[0].1.contains("\npub mod types;\n"));
+  /// assert!(files[1].1.starts_with("//! Contains all the GL types.\n"));
+  /// ```
+  pub fn module_files(&self) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    self
+      .bindings_module()
+      .and_then(|root| root.write_files("lib.rs", &mut files))
+      .expect("writing to a `String` can't fail");
+    files
+  }
+
+  /// Lays out the bindings as a tree of modules, with the code just as the
+  /// templates spell it, before [`layout_rust_source`] tidies it up.
+  fn bindings_module(&self) -> Result<BindingsModule, core::fmt::Error> {
+    let mut root = Vec::new();
+    let mut f = String::new();
     let api = self.api;
-    let major_version_number = self.version.0;
+    let major_version_number = self.version.major;
     const EXAMPLE_MODE: bool = false;
     if !EXAMPLE_MODE {
       show!(f, "#![no_std]");
@@ -173,18 +387,27 @@ impl core::fmt::Display for GlApiSelection {
     show!(f, "#![deny(missing_debug_implementations)]");
     show!(f, "#![cfg_attr(docs_rs, feature(doc_cfg))]");
     show!(f);
-    show!(
-      f,
-      "//! Bindings to {:?} {}.{}",
-      self.api,
-      self.version.0,
-      self.version.1
-    );
+    let mut targets = String::new();
+    for (i, (api, version)) in self.targets().iter().enumerate() {
+      if i != 0 {
+        targets.push_str(" + ");
+      }
+      let _cant_fail = write!(targets, "{:?} {}", api, version);
+    }
+    show!(f, "//! Bindings to {}", targets);
     show!(f, "{}", GENERATED_BY);
     show!(f, "//! Included Extensions (activate via cargo feature):");
     for ext in self.extensions.iter() {
       show!(f, "//! * `{ext}`", ext = ext);
     }
+    let api_gate_features = self.api_gate_features();
+    if !api_gate_features.is_empty() {
+      show!(f, "//!");
+      show!(f, "//! Items that only some of the APIs have are behind that API's cargo feature:");
+      for api_feature in api_gate_features {
+        show!(f, "//! * `{api_feature}`", api_feature = api_feature);
+      }
+    }
     show!(f, "{}", STANDARD_DOCS);
     if EXAMPLE_MODE {
       show!(f, "fn main() {{ }} // TODO: disable EXAMPLE_MODE.");
@@ -232,37 +455,80 @@ impl core::fmt::Display for GlApiSelection {
     #[allow(dead_code)]type APcv = AtomicPtr<c_void>;
     #[cfg(feature=\"global_loader\")]const fn ap_null() -> APcv {{ AtomicPtr::new(null_mut()) }}"
     );
+    root.push(ModulePart::Code(f));
 
     // do types
-    show!(f);
-    show!(f, "pub use types::*;");
-    show!(f, "#[allow(missing_docs)] pub mod types {{");
+    let mut f = String::new();
     show!(f, "//! Contains all the GL types.");
     show!(f, "  use super::*;");
     for gl_type in self.gl_types.iter() {
       show!(f, "  {}", gl_type);
     }
-    show!(f, "}}");
+    root.push(ModulePart::Module(BindingsModule {
+      name: String::from("types"),
+      cfg: String::new(),
+      attrs: "#[allow(missing_docs)] ",
+      public: true,
+      glob_use: true,
+      parts: vec![ModulePart::Code(f)],
+    }));
 
     // do enums
-    show!(f);
-    show!(f, "pub use enums::*;");
-    show!(f, "pub mod enums {{");
+    let mut f = String::new();
+    let mut by_extension: BTreeMap<&str, String> = BTreeMap::new();
     show!(f, "//! Contains all the GL enumerated values.");
     show!(f, "//! ");
     show!(f, "//! In C these are called 'enums', but in Rust we call them a 'const'. Whatever.");
     show!(f, "  use super::*;");
-    let mut enum_list: Vec<GlEnum> = self.gl_enums.values().cloned().collect();
-    enum_list.sort_by_key(|gl_enum| gl_enum.name.clone());
-    for gl_enum in enum_list.iter() {
-      show!(f, "  {}", GlEnumDisplayer { gl_enum, api });
+    for gl_enum in self.gl_enums.values() {
+      let dst = match self.extension_module_of(gl_enum.extensions.as_ref()) {
+        Some(ext) => by_extension.entry(ext).or_default(),
+        None => &mut f,
+      };
+      show!(dst, "  {}", GlEnumDisplayer { gl_enum, api });
+    }
+    root.push(ModulePart::Module(BindingsModule {
+      name: String::from("enums"),
+      cfg: String::new(),
+      attrs: "",
+      public: true,
+      glob_use: true,
+      parts: extension_module_parts(f, by_extension, "enumerated values", true),
+    }));
+
+    // do enum group types
+    let typed_groups = self.typed_groups();
+    if !typed_groups.is_empty() {
+      let mut f = String::new();
+      show!(f, "//! Contains a type for each group of enumerated values that a GL command accepts.");
+      show!(f, "//! ");
+      show!(f, "//! Each type has the group's values as associated constants. The field is public, so you can also wrap any other value if you need to.");
+      show!(f, "  use super::*;");
+      for (group_name, inner_ty) in typed_groups.iter() {
+        show!(
+          f,
+          "  {}",
+          GlGroupDisplayer {
+            gl_group: &self.gl_groups[*group_name],
+            inner_ty,
+            gl_enums: &self.gl_enums,
+          }
+        );
+      }
+      root.push(ModulePart::Module(BindingsModule {
+        name: String::from("groups"),
+        cfg: String::new(),
+        attrs: "",
+        public: true,
+        glob_use: true,
+        parts: vec![ModulePart::Code(f)],
+      }));
     }
-    show!(f, "}}");
 
     // ready the common elements.
-    let mut command_list: Vec<GlCommand> =
+    let command_list: Vec<GlCommand> =
       self.gl_commands.values().cloned().collect();
-    command_list.sort_by_key(|gl_command| gl_command.name.clone());
+    let mut f = String::new();
     show!(
       f,
       "
@@ -276,32 +542,191 @@ fn go_panic_because_fn_not_loaded(name: &str) -> ! {{
   panic!(\"called {{name}} but it was not loaded.\", name = name)
 }}
 
-/// Loads a function pointer.
+/// Loads a function pointer, trying each of its names in turn until one loads.
 /// Rejects suggested pointer addresses which are likely to be lies.
 /// This function is used by both the global loader and struct loader.
 /// We mark it as `inline(never)` to favor a small binary over initialization speed.
-/// Returns if there's now a non-null value in the atomic pointer.
+/// Returns [`LoadOutcome::Loaded`] if there's now a non-null value in the atomic pointer,
+/// along with the index of the name that it was loaded by.
 #[inline(never)]#[allow(dead_code)]
 fn load_dyn_name_atomic_ptr(
   get_proc_address: &mut dyn FnMut(*const c_char) -> *mut c_void,
-  fn_name: &[u8],
+  fn_names: &[&[u8]],
   ptr: &APcv,
-) -> bool {{
-  // if this fails the code generator itself royally screwed up somehow,
-  // and so it's only a debug assert.
-  debug_assert_eq!(*fn_name.last().unwrap(), 0);
-  let p: *mut c_void = get_proc_address(fn_name.as_ptr() as *const c_char);
-  let p_usize = p as usize;
-  // You *should* get null for failed lookups, but some systems have been
-  // reported to give \"error code\" values such as -1 or small non-null values.
-  // To help guard against this silliness, we consider these values to also
-  // just be a result of null.
-  if p_usize == core::usize::MAX || p_usize < 8 {{
-    ptr.store(null_mut(), RELAX);
-    false
-  }} else {{
-    ptr.store(p, RELAX);
-    true
+) -> (LoadOutcome, usize) {{
+  let mut outcome = LoadOutcome::Missing;
+  for (i, fn_name) in fn_names.iter().enumerate() {{
+    // if this fails the code generator itself royally screwed up somehow,
+    // and so it's only a debug assert.
+    debug_assert_eq!(*fn_name.last().unwrap(), 0);
+    let p: *mut c_void = get_proc_address(fn_name.as_ptr() as *const c_char);
+    let p_usize = p as usize;
+    // You *should* get null for failed lookups, but some systems have been
+    // reported to give \"error code\" values such as -1 or small non-null values.
+    // To help guard against this silliness, we consider these values to also
+    // just be a result of null.
+    if p.is_null() {{
+      continue;
+    }} else if p_usize == core::usize::MAX || p_usize < 8 {{
+      outcome = LoadOutcome::Rejected;
+    }} else {{
+      ptr.store(p, RELAX);
+      return (LoadOutcome::Loaded, i);
+    }}
+  }}
+  ptr.store(null_mut(), RELAX);
+  (outcome, 0)
+}}
+
+/// What happened when loading one GL function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadOutcome {{
+  /// It wasn't loaded, because the features it needs aren't enabled.
+  Skipped,
+  /// A pointer was loaded.
+  Loaded,
+  /// The lookup gave a null pointer.
+  Missing,
+  /// The lookup gave an address that's likely to be a lie, such as `-1`, so
+  /// it was treated like a null pointer.
+  Rejected,
+}}
+
+/// What happened to each GL function when a loader ran.
+///
+/// You can log this at startup, or check that everything you need is there
+/// before going any further. The `Debug` output has the counts, plus the names
+/// of anything missing or rejected, and of anything loaded by one of its
+/// aliases.
+#[derive(Clone)]
+pub struct LoadReport {{
+  outcomes: [LoadOutcome; GL_COMMAND_NAMES.len()],
+  /// Which name each function was loaded by: 0 for its own name, otherwise
+  /// one past the index into its aliases.
+  name_indexes: [u8; GL_COMMAND_NAMES.len()],
+}}
+impl LoadReport {{
+  #[allow(dead_code)]
+  const fn new() -> Self {{
+    Self {{
+      outcomes: [LoadOutcome::Skipped; GL_COMMAND_NAMES.len()],
+      name_indexes: [0; GL_COMMAND_NAMES.len()],
+    }}
+  }}
+
+  /// Records what `load_dyn_name_atomic_ptr` gave for the function at `i`.
+  #[allow(dead_code)]
+  fn record(&mut self, i: usize, (outcome, name_index): (LoadOutcome, usize)) {{
+    self.outcomes[i] = outcome;
+    self.name_indexes[i] = name_index as u8;
+  }}
+
+  /// What happened when loading the named function, or `None` if it's not
+  /// part of these bindings.
+  pub fn outcome(&self, name: &str) -> Option<LoadOutcome> {{
+    let i = GL_COMMAND_NAMES.binary_search_by(|probe| (*probe).cmp(name)).ok()?;
+    Some(self.outcomes[i])
+  }}
+
+  /// The names of the functions with the outcome given, in sorted order.
+  pub fn names_with(&self, outcome: LoadOutcome) -> impl Iterator<Item = &'static str> + '_ {{
+    GL_COMMAND_NAMES.iter().zip(self.outcomes.iter()).filter(move |(_, o)| **o == outcome).map(|(name, _)| *name)
+  }}
+
+  /// The number of functions with the outcome given.
+  pub fn count(&self, outcome: LoadOutcome) -> usize {{
+    self.outcomes.iter().filter(|o| **o == outcome).count()
+  }}
+
+  /// The names of the functions that loaded.
+  pub fn loaded(&self) -> impl Iterator<Item = &'static str> + '_ {{
+    self.names_with(LoadOutcome::Loaded)
+  }}
+
+  /// The names of the functions that the lookup didn't find.
+  pub fn missing(&self) -> impl Iterator<Item = &'static str> + '_ {{
+    self.names_with(LoadOutcome::Missing)
+  }}
+
+  /// The names of the functions that the lookup gave a bogus address for.
+  pub fn rejected(&self) -> impl Iterator<Item = &'static str> + '_ {{
+    self.names_with(LoadOutcome::Rejected)
+  }}
+
+  /// The number of functions that loaded.
+  pub fn loaded_count(&self) -> usize {{
+    self.count(LoadOutcome::Loaded)
+  }}
+
+  /// The number of functions that the lookup didn't find.
+  pub fn missing_count(&self) -> usize {{
+    self.count(LoadOutcome::Missing)
+  }}
+
+  /// The number of functions that the lookup gave a bogus address for.
+  pub fn rejected_count(&self) -> usize {{
+    self.count(LoadOutcome::Rejected)
+  }}
+
+  /// The name that the named function was actually loaded by, which is one
+  /// of its aliases if its own name didn't load.
+  ///
+  /// This is `None` if the function isn't loaded, or isn't part of these
+  /// bindings.
+  pub fn bound_name(&self, name: &str) -> Option<&'static str> {{
+    let i = GL_COMMAND_NAMES.binary_search_by(|probe| (*probe).cmp(name)).ok()?;
+    self.bound_name_at(i)
+  }}
+
+  fn bound_name_at(&self, i: usize) -> Option<&'static str> {{
+    if self.outcomes[i] != LoadOutcome::Loaded {{
+      return None;
+    }}
+    let name = GL_COMMAND_NAMES[i];
+    match self.name_indexes[i] {{
+      0 => Some(name),
+      n => {{
+        let aliases = GL_COMMAND_ALIASES.binary_search_by(|(probe, _)| (*probe).cmp(name)).ok()?;
+        GL_COMMAND_ALIASES[aliases].1.get(usize::from(n) - 1).copied()
+      }}
+    }}
+  }}
+
+  /// The functions that were loaded by one of their aliases, as `(name,
+  /// bound_name)` pairs.
+  pub fn aliased(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {{
+    (0..GL_COMMAND_NAMES.len()).filter(move |i| self.name_indexes[*i] != 0).filter_map(move |i| Some((GL_COMMAND_NAMES[i], self.bound_name_at(i)?)))
+  }}
+
+  /// If every function that was looked up got loaded.
+  pub fn is_complete(&self) -> bool {{
+    self.outcomes.iter().all(|o| matches!(o, LoadOutcome::Loaded | LoadOutcome::Skipped))
+  }}
+}}
+impl core::fmt::Debug for LoadReport {{
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{
+    /// Lists the names with an outcome, without needing to collect them.
+    struct Names<'r>(&'r LoadReport, LoadOutcome);
+    impl core::fmt::Debug for Names<'_> {{
+      fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{
+        f.debug_list().entries(self.0.names_with(self.1)).finish()
+      }}
+    }}
+    /// Lists the functions loaded by an alias, as `name => bound_name`.
+    struct Aliased<'r>(&'r LoadReport);
+    impl core::fmt::Debug for Aliased<'_> {{
+      fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{
+        f.debug_map().entries(self.0.aliased()).finish()
+      }}
+    }}
+    f.debug_struct(\"LoadReport\")
+      .field(\"loaded_count\", &self.loaded_count())
+      .field(\"missing_count\", &self.missing_count())
+      .field(\"rejected_count\", &self.rejected_count())
+      .field(\"missing\", &Names(self, LoadOutcome::Missing))
+      .field(\"rejected\", &Names(self, LoadOutcome::Rejected))
+      .field(\"aliased\", &Aliased(self))
+      .finish()
   }}
 }}
 
@@ -323,12 +748,268 @@ fn report_error_code_from(name: &str, err: GLenum) {{
 }}"
     );
 
+    // the names that a `LoadReport` lists, in the same order as
+    // `command_list`.
+    show!(f, "/// The name of every GL function, sorted.");
+    show!(f, "const GL_COMMAND_NAMES: [&str; {}] = [", command_list.len());
+    for gl_command in command_list.iter() {
+      show!(f, "  \"{}\",", gl_command.name);
+    }
+    show!(f, "];");
+    show!(f, "/// The other names that functions can be loaded by, sorted by function name.");
+    show!(
+      f,
+      "/// Each function's aliases are in the order that the loaders try them."
+    );
+    let aliased_commands: Vec<&GlCommand> = command_list
+      .iter()
+      .filter(|gl_command| !gl_command.load_aliases.is_empty())
+      .collect();
+    show!(
+      f,
+      "const GL_COMMAND_ALIASES: [(&str, &[&str]); {}] = [",
+      aliased_commands.len()
+    );
+    for gl_command in aliased_commands {
+      show!(
+        f,
+        "  (\"{}\", &[\"{}\"]),",
+        gl_command.name,
+        gl_command.load_aliases.join("\", \"")
+      );
+    }
+    show!(f, "];");
+
+    if self.has_context_info() {
+      show!(
+        f,
+        "
+/// What the current GL context says about itself.
+///
+/// Get one from `query_global_context_info` or `GlFns::query_context_info`
+/// once the functions are loaded and the context is current.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ContextInfo {{
+  /// The context's `(major, minor)` version, so you can compare it like
+  /// `info.version >= (3, 3)`.
+  pub version: (u32, u32),
+  /// If this is an OpenGL ES context.
+  pub is_es: bool,"
+      );
+      for ext in self.extensions.iter() {
+        show!(f, "  /// If the context supports `{ext}`.", ext = ext);
+        show!(f, "  pub has_{ext}: bool,", ext = ext);
+      }
+      show!(
+        f,
+        "}}
+impl ContextInfo {{
+  /// Sets the flag for an extension name that the context listed.
+  #[allow(dead_code)]
+  fn note_extension(&mut self, name: &[u8]) {{
+    match name {{"
+      );
+      for ext in self.extensions.iter() {
+        show!(f, "      b\"{ext}\" => self.has_{ext} = true,", ext = ext);
+      }
+      show!(
+        f,
+        "      _ => (),
+    }}
+  }}
+}}
+
+/// The bytes of a C string, or nothing for a null pointer.
+#[allow(dead_code)]
+unsafe fn c_str_bytes<'a>(p: *const GLubyte) -> &'a [u8] {{
+  if p.is_null() {{
+    return &[];
+  }}
+  let p = p as *const u8;
+  let mut len = 0;
+  while *p.add(len) != 0 {{
+    len += 1;
+  }}
+  core::slice::from_raw_parts(p, len)
+}}
+
+/// Reads the decimal number at the start of `bytes`, giving it and the rest.
+#[allow(dead_code)]
+fn parse_decimal(bytes: &[u8]) -> (u32, &[u8]) {{
+  let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+  let n = bytes[..digits].iter().fold(0_u32, |n, b| n.saturating_mul(10).saturating_add(u32::from(b - b'0')));
+  (n, &bytes[digits..])
+}}
+
+/// Parses a `GL_VERSION` string, such as `4.6.0 NVIDIA 535.54` or
+/// `OpenGL ES 3.2 Mesa 23.1`, into the version and if it's an ES context.
+#[allow(dead_code)]
+fn parse_gl_version(bytes: &[u8]) -> ((u32, u32), bool) {{
+  let es_prefix: &[u8] = b\"OpenGL ES\";
+  let is_es = bytes.starts_with(es_prefix);
+  let mut rest = bytes;
+  if is_es {{
+    // ES 1 puts the profile right after the prefix, as in `OpenGL ES-CM 1.1`.
+    rest = &bytes[es_prefix.len()..];
+    rest = &rest[rest.iter().take_while(|b| **b != b' ').count()..];
+  }}
+  rest = &rest[rest.iter().take_while(|b| **b == b' ').count()..];
+  let (major, rest) = parse_decimal(rest);
+  let minor = match rest.first() {{
+    Some(b'.') => parse_decimal(&rest[1..]).0,
+    _ => 0,
+  }};
+  ((major, minor), is_es)
+}}
+
+/// Asks the context about itself, using whichever loader's pointers are given.
+#[allow(dead_code)]
+unsafe fn context_info_from(
+  get_string: &APcv,
+  get_integerv: &APcv,
+  get_stringi: Option<&APcv>,
+) -> ContextInfo {{
+  // Not every API version has these in its enums, so they're spelled out here.
+  const VERSION: GLenum = 0x1F02;
+  const EXTENSIONS: GLenum = 0x1F03;
+  const MAJOR_VERSION: GLenum = 0x821B;
+  const MINOR_VERSION: GLenum = 0x821C;
+  const NUM_EXTENSIONS: GLenum = 0x821D;
+  let mut info = ContextInfo::default();
+  let version: *const GLubyte = call_atomic_ptr_1arg(\"glGetString\", get_string, VERSION);
+  let (version, is_es) = parse_gl_version(c_str_bytes(version));
+  info.version = version;
+  info.is_es = is_es;
+  if info.version.0 >= 3 {{
+    // The integers don't depend on how the vendor formats the string.
+    let mut major: GLint = 0;
+    let mut minor: GLint = 0;
+    call_atomic_ptr_2arg::<(), _, _>(\"glGetIntegerv\", get_integerv, MAJOR_VERSION, &mut major as *mut GLint);
+    call_atomic_ptr_2arg::<(), _, _>(\"glGetIntegerv\", get_integerv, MINOR_VERSION, &mut minor as *mut GLint);
+    if major > 0 && minor >= 0 {{
+      info.version = (major as u32, minor as u32);
+    }}
+  }}
+  match get_stringi {{
+    Some(get_stringi) if info.version.0 >= 3 && !get_stringi.load(RELAX).is_null() => {{
+      let mut count: GLint = 0;
+      call_atomic_ptr_2arg::<(), _, _>(\"glGetIntegerv\", get_integerv, NUM_EXTENSIONS, &mut count as *mut GLint);
+      for i in 0..count.max(0) as GLuint {{
+        let name: *const GLubyte = call_atomic_ptr_2arg(\"glGetStringi\", get_stringi, EXTENSIONS, i);
+        info.note_extension(c_str_bytes(name));
+      }}
+    }}
+    _ => {{
+      // Core profiles don't have the one big string, but then they do have
+      // `glGetStringi`.
+      let names: *const GLubyte = call_atomic_ptr_1arg(\"glGetString\", get_string, EXTENSIONS);
+      for name in c_str_bytes(names).split(|b| *b == b' ') {{
+        info.note_extension(name);
+      }}
+    }}
+  }}
+  info
+}}"
+      );
+    }
+
+    // the lazy loader needs room to add a null to any name it tries.
+    let longest_name = command_list
+      .iter()
+      .flat_map(|gl_command| {
+        core::iter::once(&gl_command.name).chain(gl_command.load_aliases.iter())
+      })
+      .map(String::len)
+      .max()
+      .unwrap_or(0);
+    show!(
+      f,
+      "
+/// The `get_proc_address` that `set_loader` stored, if any.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+static LAZY_LOADER: LazyLoader = LazyLoader {{
+  locked: core::sync::atomic::AtomicBool::new(false),
+  get_proc_address: core::cell::UnsafeCell::new(None),
+}};
+
+/// A `get_proc_address` that `set_loader` can store.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+type StoredLoader = &'static (dyn Fn(*const c_char) -> *mut c_void + Sync);
+
+/// Holds the `get_proc_address` that `set_loader` stored.
+///
+/// It's a reference to a trait object, which is too big for an atomic, so a
+/// lock keeps it from being read while it's being replaced. The lock is only
+/// held long enough to copy the reference in or out.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+struct LazyLoader {{
+  locked: core::sync::atomic::AtomicBool,
+  get_proc_address: core::cell::UnsafeCell<Option<StoredLoader>>,
+}}
+// Safety: the cell is only touched while holding the lock.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+unsafe impl Sync for LazyLoader {{}}
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+impl LazyLoader {{
+  fn with_lock<R>(&self, op: impl FnOnce(&mut Option<StoredLoader>) -> R) -> R {{
+    let lock = || self.locked.compare_exchange_weak(false, true, Ordering::Acquire, RELAX);
+    while lock().is_err() {{
+      core::hint::spin_loop();
+    }}
+    // Safety: we hold the lock.
+    let out = op(unsafe {{ &mut *self.get_proc_address.get() }});
+    self.locked.store(false, Ordering::Release);
+    out
+  }}
+}}
+
+/// The length of the longest name that a function can be loaded by.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+const LONGEST_NAME_LEN: usize = {longest_name};
+
+/// Loads a global function's pointer if it isn't loaded yet, so that the
+/// first call of each global function loads it. `GlFns` never does this.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+#[inline(always)]
+fn lazy_load_if_null(name: &str, ptr: &APcv) {{
+  if ptr.load(RELAX).is_null() {{
+    lazy_load(name, ptr);
+  }}
+}}
+
+/// Loads a function pointer by trying its own name and then its aliases with
+/// the `get_proc_address` that `set_loader` stored.
+///
+/// The pointer stays null if there's no loader or it didn't find any of the
+/// names.
+#[cfg(all(feature = \"global_loader\", feature = \"lazy_loader\"))]
+#[cold]
+#[inline(never)]
+fn lazy_load(name: &str, ptr: &APcv) {{
+  let mut get_proc_address = match LAZY_LOADER.with_lock(|stored| *stored) {{
+    Some(get_proc_address) => get_proc_address,
+    None => return,
+  }};
+  let aliases: &[&str] = match GL_COMMAND_ALIASES.binary_search_by(|(probe, _)| (*probe).cmp(name)) {{
+    Ok(i) => GL_COMMAND_ALIASES[i].1,
+    Err(_) => &[],
+  }};
+  for load_name in core::iter::once(name).chain(aliases.iter().copied()) {{
+    let mut fn_name = [0_u8; LONGEST_NAME_LEN + 1];
+    fn_name[..load_name.len()].copy_from_slice(load_name.as_bytes());
+    let fn_name = &fn_name[..=load_name.len()];
+    if load_dyn_name_atomic_ptr(&mut get_proc_address, &[fn_name], ptr).0 == LoadOutcome::Loaded {{
+      return;
+    }}
+  }}
+}}",
+      longest_name = longest_name
+    );
+
     // add generic fn callers
-    let arity_set: HashSet<_> =
+    let arity_set: BTreeSet<_> =
       command_list.iter().map(|glc| glc.params.len()).collect();
-    let mut arity_list: Vec<_> = arity_set.iter().copied().collect();
-    arity_list.sort();
-    for arity in arity_list.iter().copied() {
+    for arity in arity_set.iter().copied() {
       let mut param_generics = String::new();
       let mut param_names_and_types = String::new();
       let mut param_names = String::new();
@@ -356,7 +1037,7 @@ fn report_error_code_from(name: &str, err: GLenum) {{
         f,
         "
   #[inline(always)]#[allow(dead_code)]
-  unsafe fn call_atomic_ptr_{arity}arg<Ret{ret_comma}{param_generics}>(name: &str, ptr: &APcv, {param_names_and_types}) -> Ret {{
+  unsafe fn call_atomic_ptr_{arity}arg<Ret{ret_comma}{param_generics}>(name: &str, ptr: &APcv{ret_comma} {param_names_and_types}) -> Ret {{
     let p = ptr.load(RELAX);
     match transmute::<*mut c_void, Option<extern \"system\" fn({param_generics})->Ret>>(p) {{
       Some(fn_p) => fn_p({param_names}),
@@ -371,10 +1052,10 @@ fn report_error_code_from(name: &str, err: GLenum) {{
       );
     }
 
+    root.push(ModulePart::Code(f));
+
     // do global commands
-    show!(f);
-    show!(f, "#[cfg(feature=\"global_loader\")] pub use global_commands::*;");
-    show!(f, "#[cfg(feature=\"global_loader\")] pub mod global_commands {{");
+    let mut f = String::new();
     show!(f, "//! Contains functions for using the global GL loader.");
     show!(f, "  use super::*;");
     show!(
@@ -397,51 +1078,142 @@ fn report_error_code_from(name: &str, err: GLenum) {{
   /// This allows you to call [SDL_GL_GetProcAddress](https://wiki.libsdl.org/SDL_GL_GetProcAddress),
   /// [wglGetProcAddress](https://docs.microsoft.com/en-us/windows/win32/api/wingdi/nf-wingdi-wglgetprocaddress),
   /// or some similar function, depending on your OS.
+  ///
+  /// The [`LoadReport`] says which functions loaded and which didn't.
   pub unsafe fn load_global_gl_with<F>(
     mut get_proc_address: F,
-  )
+  ) -> LoadReport
   where
     F: FnMut(*const c_char) -> *mut c_void
-  {{"
+  {{
+    let mut report = LoadReport::new();"
     );
-    for gl_command in command_list.iter() {
-      match gl_command.extensions.as_ref() {
-        None => show!(
+    for (i, gl_command) in command_list.iter().enumerate() {
+      let InfoForGlCommandPrinting {
+        extensions,
+        atomic_ptr_name,
+        load_names,
+        ..
+      } = InfoForGlCommandPrinting::from_command_and_api(
+        gl_command,
+        self.api,
+        self.version.major,
+        false,
+        &typed_groups,
+      );
+      let load = format!(
+        "report.record({i}, load_dyn_name_atomic_ptr(&mut get_proc_address, {load_names}, &{atomic_ptr_name}));",
+        i = i,
+        load_names = load_names,
+        atomic_ptr_name = atomic_ptr_name,
+      );
+      if gl_command.extensions.is_none() && gl_command.apis.is_none() {
+        show!(f, "    {}", load);
+      } else {
+        show!(
           f,
-          "    {name}_load_with_dyn(&mut get_proc_address) as usize;",
-          name = gl_command.name
-        ),
-        Some(_) => {
-          let InfoForGlCommandPrinting { extensions, .. } =
-            InfoForGlCommandPrinting::from_command_and_api(
-              gl_command,
-              self.api,
-              self.version.0,
-              false,
-            );
-          show!(
-            f,
-            "    {extensions}{{
-              {name}_load_with_dyn(&mut get_proc_address) as usize;
+          "    {extensions}{{
+              {load}
             }}",
-            name = gl_command.name,
-            extensions = extensions,
-          )
-        }
+          load = load,
+          extensions = extensions,
+        )
       }
     }
     // close load_global_gl_with
+    show!(f, "    report");
     show!(f, "}}");
+    show!(
+      f,
+      "
+  /// Stores the `get_proc_address` for each global function to load itself
+  /// with, the first time that it's called.
+  ///
+  /// After this, calling a function that isn't loaded yet only panics if
+  /// `get_proc_address` doesn't find it by its name or any of its aliases.
+  /// Until a function has been called, its `_is_loaded` check still says
+  /// that it isn't loaded. You can also still call [`load_global_gl_with`]
+  /// to load everything up front.
+  ///
+  /// Calling this again replaces the stored function, but doesn't reload
+  /// anything that's already loaded.
+  ///
+  /// The function is kept in a static, so it has to live forever and be
+  /// `Sync`. A plain `fn` can be given as `set_loader(&get_proc_address)`,
+  /// and a closure that captures the loader you were given (such as glutin's
+  /// `Display::get_proc_address`) can be leaked with `Box::leak` if it's
+  /// `Sync`. If it isn't, use [`load_global_gl_with`] instead.
+  #[cfg(feature = \"lazy_loader\")]
+  #[cfg_attr(docs_rs, doc(cfg(feature = \"lazy_loader\")))]
+  pub fn set_loader(
+    get_proc_address: &'static (dyn Fn(*const c_char) -> *mut c_void + Sync),
+  ) {{
+    LAZY_LOADER.with_lock(|stored| *stored = Some(get_proc_address));
+  }}"
+    );
+    if self.has_context_info() {
+      show!(
+        f,
+        "
+  /// Asks the current context for its version and which of the extensions in
+  /// these bindings it supports.
+  ///
+  /// Call this after [`load_global_gl_with`] (or `set_loader`, with the
+  /// `lazy_loader` feature), while the context is current.
+  pub unsafe fn query_global_context_info() -> ContextInfo {{
+    #[cfg(feature = \"lazy_loader\")]
+    {{
+      lazy_load_if_null(\"glGetString\", &glGetString_p);
+      lazy_load_if_null(\"glGetIntegerv\", &glGetIntegerv_p);
+      {lazy_get_stringi}
+    }}
+    {body}
+  }}",
+        lazy_get_stringi = match self.gl_commands.get("glGetStringi") {
+          Some(gl_command) => format!(
+            "{}lazy_load_if_null(\"glGetStringi\", &glGetStringi_p);",
+            item_cfg_statement(
+              gl_command.apis.as_ref(),
+              gl_command.extensions.as_ref(),
+              gl_command.core_apis.as_ref(),
+            )
+          ),
+          None => String::new(),
+        },
+        body = self.context_info_body("")
+      );
+    }
+    show!(f, "{}", self.partial_loaders(false)?);
+    let mut by_extension: BTreeMap<&str, String> = BTreeMap::new();
     for gl_command in command_list.iter() {
-      show!(f);
-      show!(f, "{}", GlobalGlCommand { gl_command, api, major_version_number });
+      let dst = match self.extension_module_of(gl_command.extensions.as_ref()) {
+        Some(ext) => by_extension.entry(ext).or_default(),
+        None => &mut f,
+      };
+      show!(dst);
+      show!(
+        dst,
+        "{}",
+        GlobalGlCommand {
+          gl_command,
+          api,
+          major_version_number,
+          typed_groups: &typed_groups
+        }
+      );
     }
-    show!(f, "}}");
+    root.push(ModulePart::Module(BindingsModule {
+      name: String::from("global_commands"),
+      cfg: String::from("#[cfg(feature=\"global_loader\")] "),
+      attrs: "",
+      public: true,
+      glob_use: true,
+      parts: extension_module_parts(f, by_extension, "global functions", true),
+    }));
 
     // do struct commands
-    show!(f);
-    show!(f, "#[cfg(feature=\"struct_loader\")] pub use struct_commands::*;");
-    show!(f, "#[cfg(feature=\"struct_loader\")] pub mod struct_commands {{");
+    let mut f = String::new();
+    let mut by_extension: BTreeMap<&str, String> = BTreeMap::new();
     show!(f, "//! Contains the [`GlFns`] type for using the struct GL loader.");
     show!(f, "  use super::*;");
     show!(
@@ -450,91 +1222,566 @@ fn report_error_code_from(name: &str, err: GLenum) {{
       StructLoaderDisplayer {
         gl_commands: &command_list,
         api,
-        major_version_number
+        major_version_number,
+        typed_groups: &typed_groups,
+        methods_elsewhere: &|gl_command: &GlCommand| {
+          self.extension_module_of(gl_command.extensions.as_ref()).is_some()
+        },
       }
     );
-    show!(f, "}}");
-    show!(f, "// end of module");
-    Ok(())
-  }
-}
-impl GlApiSelection {
-  /// This is how you select a specific API level and profile and all that out
-  /// of a GlRegistry.
-  pub fn new_from_registry_api_extensions(
-    reg: &GlRegistry, api: ApiGroup, level: (i32, i32),
-    target_profile: GlProfile, extensions: &[&str],
-  ) -> Self {
-    let gl_types: Vec<GlType> = reg.gl_types.clone();
-    let mut gl_enums: HashMap<String, GlEnum> = HashMap::new();
-    let mut gl_commands: HashMap<String, GlCommand> = HashMap::new();
-    let target_number = format!("{}.{}", level.0, level.1);
-    //
-    for gl_feature in reg.gl_features.iter() {
-      if gl_feature.api != api || gl_feature.number > target_number {
-        continue;
-      }
-      for GlRequirement { profile, api, adjustment } in
-        gl_feature.required.iter()
+    if self.has_context_info() {
+      show!(
+        f,
+        "  impl GlFns {{
+    /// Asks the current context for its version and which of the extensions
+    /// in these bindings it supports.
+    ///
+    /// The context must be current, and the same one that these functions
+    /// were loaded from.
+    pub unsafe fn query_context_info(&self) -> ContextInfo {{
+      {body}
+    }}
+  }}",
+        body = self.context_info_body("self.")
+      );
+    }
+    show!(f, "  impl GlFns {{\n{}  }}", self.partial_loaders(true)?);
+    for gl_command in command_list.iter() {
+      if let Some(ext) =
+        self.extension_module_of(gl_command.extensions.as_ref())
       {
-        if let Some(p) = profile {
-          match p.as_str() {
-            "core" => {
-              if target_profile != GlProfile::Core {
-                continue;
-              }
-            }
-            "compatibility" => {
-              if target_profile != GlProfile::Compatibility {
-                continue;
-              }
-            }
-            unknown => panic!("unknown: {}", unknown),
+        show!(
+          by_extension.entry(ext).or_default(),
+          "{}",
+          GlFnsMethods {
+            gl_command,
+            api,
+            major_version_number,
+            typed_groups: &typed_groups,
+          }
+        );
+      }
+    }
+    for methods in by_extension.values_mut() {
+      *methods = format!("  impl GlFns {{\n{}  }}\n", methods);
+    }
+    root.push(ModulePart::Module(BindingsModule {
+      name: String::from("struct_commands"),
+      cfg: String::from("#[cfg(feature=\"struct_loader\")] "),
+      attrs: "",
+      public: true,
+      glob_use: true,
+      parts: extension_module_parts(
+        f,
+        by_extension,
+        "[`GlFns`] methods",
+        // it's just `impl` blocks, so there's nothing to re-export.
+        false,
+      ),
+    }));
+    root.push(ModulePart::Code(String::from("// end of module\n")));
+    Ok(BindingsModule {
+      name: String::new(),
+      cfg: String::new(),
+      attrs: "",
+      public: true,
+      glob_use: true,
+      parts: root,
+    })
+  }
+
+  /// The extension whose module an item goes in, if the item should go in one.
+  ///
+  /// That's when [`extension_modules`](GeneratorOptions::extension_modules)
+  /// is on and exactly one extension adds the item.
+  fn extension_module_of<'e>(
+    &self, extensions: Option<&'e Vec<String>>,
+  ) -> Option<&'e str> {
+    match extensions {
+      Some(extensions)
+        if self.generator.extension_modules && extensions.len() == 1 =>
+      {
+        Some(extensions[0].as_str())
+      }
+      _ => None,
+    }
+  }
+
+  /// The functions that load just what one extension or version adds, for
+  /// the struct loader or the global loader.
+  fn partial_loaders(
+    &self, struct_mode: bool,
+  ) -> Result<String, core::fmt::Error> {
+    let mut f = String::new();
+    for extension in self.extensions.iter() {
+      let names =
+        self.extension_commands.get(extension).map_or(&[][..], Vec::as_slice);
+      self.write_partial_loader(
+        &mut f,
+        struct_mode,
+        &format!("load_{}_with", extension),
+        &format!("that `{}` requires", extension),
+        Some(extension),
+        names,
+      )?;
+    }
+    for (version, names) in self.version_commands.iter() {
+      self.write_partial_loader(
+        &mut f,
+        struct_mode,
+        &format!("load_version_{}_{}_with", version.major, version.minor),
+        &format!("that version {} adds", version),
+        None,
+        names,
+      )?;
+    }
+    Ok(f)
+  }
+
+  /// Writes a function that loads only the commands `names`, giving if all
+  /// of them loaded. If it's for an `extension`, it's gated on that
+  /// extension.
+  fn write_partial_loader(
+    &self, f: &mut String, struct_mode: bool, fn_name: &str, which: &str,
+    extension: Option<&String>, names: &[String],
+  ) -> core::fmt::Result {
+    let cfg = item_cfg(
+      None,
+      extension.map(|extension| vec![extension.clone()]).as_ref(),
+      None,
+    );
+    let mut loads = String::new();
+    let mut any_gated = false;
+    for gl_command in names.iter().filter_map(|name| self.gl_commands.get(name))
+    {
+      let load = if struct_mode {
+        format!(
+          "all_loaded &= self.{}_load_with_dyn(&mut get_proc_address);",
+          &gl_command.name[2..]
+        )
+      } else {
+        format!(
+          "all_loaded &= {}_load_with_dyn(&mut get_proc_address);",
+          gl_command.name
+        )
+      };
+      // the function's own gate covers the command's if it's one of the
+      // extensions that the command needs.
+      let gated_by_extension = gl_command.apis.is_none()
+        && extension.is_some_and(|extension| {
+          gl_command.extensions.iter().flatten().any(|e| e == extension)
+        });
+      match item_cfg_statement(
+        gl_command.apis.as_ref(),
+        gl_command.extensions.as_ref(),
+        gl_command.core_apis.as_ref(),
+      ) {
+        statement_cfg if statement_cfg.is_empty() || gated_by_extension => {
+          show!(loads, "  {}", load)
+        }
+        statement_cfg => {
+          any_gated = true;
+          show!(loads, "  {}{{ {} }}", statement_cfg, load)
+        }
+      }
+    }
+    if loads.is_empty() {
+      write!(
+        f,
+        "
+  /// Loads just the functions {which}, giving if all of them loaded.
+  ///
+  /// There aren't any, so this is always `true`.
+  {cfg}pub unsafe fn {fn_name}<F>(
+    {self_param}_get_proc_address: F,
+  ) -> bool
+  where
+    F: FnMut(*const c_char) -> *mut c_void
+  {{
+    true
+  }}",
+        which = which,
+        cfg = cfg,
+        fn_name = fn_name,
+        self_param = if struct_mode { "&self,\n    " } else { "" },
+      )
+    } else {
+      write!(
+        f,
+        "
+  /// Loads just the functions {which}, giving if all of them loaded.
+  {cfg}{allow}pub unsafe fn {fn_name}<F>(
+    {self_param}mut get_proc_address: F,
+  ) -> bool
+  where
+    F: FnMut(*const c_char) -> *mut c_void
+  {{
+    let mut all_loaded = true;
+{loads}    all_loaded
+  }}",
+        which = which,
+        cfg = cfg,
+        allow = if any_gated {
+          // the features can leave out every load.
+          "#[allow(unused_mut, unused_variables)]"
+        } else {
+          ""
+        },
+        fn_name = fn_name,
+        self_param = if struct_mode { "&self,\n    " } else { "" },
+        loads = loads,
+      )
+    }
+  }
+
+  /// If the bindings get a `ContextInfo`, which needs `glGetString` and
+  /// `glGetIntegerv` no matter which features are on.
+  fn has_context_info(&self) -> bool {
+    ["glGetString", "glGetIntegerv"].iter().all(|name| {
+      self.gl_commands.get(*name).is_some_and(|gl_command| {
+        gl_command.apis.is_none() && gl_command.extensions.is_none()
+      })
+    })
+  }
+
+  /// The body of a function that makes a `ContextInfo`, with `owner` being
+  /// where the atomic pointers are (such as `self.`).
+  fn context_info_body(&self, owner: &str) -> String {
+    let call = |get_stringi: &str| {
+      format!(
+        "context_info_from(&{owner}glGetString_p, &{owner}glGetIntegerv_p, {get_stringi})",
+        owner = owner,
+        get_stringi = get_stringi
+      )
+    };
+    match self.gl_commands.get("glGetStringi") {
+      None => call("None"),
+      Some(gl_command) => {
+        let get_stringi = format!("Some(&{}glGetStringi_p)", owner);
+        match item_cfg_predicate(
+          gl_command.apis.as_ref(),
+          gl_command.extensions.as_ref(),
+          gl_command.core_apis.as_ref(),
+        ) {
+          None => call(&get_stringi),
+          Some(predicate) => format!(
+            "#[cfg({predicate})]let get_stringi = {get_stringi};\n#[cfg(not({predicate}))]let get_stringi = None;\n{call}",
+            predicate = predicate,
+            get_stringi = get_stringi,
+            call = call("get_stringi")
+          ),
+        }
+      }
+    }
+  }
+}
+
+/// A module of the generated bindings, with the code just as the templates
+/// spell it.
+struct BindingsModule {
+  name: String,
+  /// Attributes for both the module and its glob re-export.
+  cfg: String,
+  /// Attributes for just the module.
+  attrs: &'static str,
+  /// If the module is `pub`, or only there to hold part of the code.
+  public: bool,
+  /// If the parent re-exports everything in the module.
+  glob_use: bool,
+  parts: Vec<ModulePart>,
+}
+
+enum ModulePart {
+  Code(String),
+  Module(BindingsModule),
+}
+
+impl BindingsModule {
+  /// Writes out the module's contents, with each submodule inline.
+  fn write_inline(&self, f: &mut String) -> core::fmt::Result {
+    for part in self.parts.iter() {
+      match part {
+        ModulePart::Code(code) => f.push_str(code),
+        ModulePart::Module(module) => {
+          module.write_declaration(f, " {")?;
+          module.write_inline(f)?;
+          show!(f, "}}");
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Adds a file at `path` with the module's contents, then a file for each
+  /// submodule in the folder that `rustc` looks for them in.
+  fn write_files(
+    &self, path: &str, files: &mut Vec<(String, String)>,
+  ) -> core::fmt::Result {
+    let folder = match path.strip_suffix(".rs") {
+      Some(module_path) if path != "lib.rs" => format!("{}/", module_path),
+      _ => String::new(),
+    };
+    let mut f = String::new();
+    let mut submodules = Vec::new();
+    for part in self.parts.iter() {
+      match part {
+        ModulePart::Code(code) => f.push_str(code),
+        ModulePart::Module(module) => {
+          module.write_declaration(&mut f, ";")?;
+          submodules.push((format!("{}{}.rs", folder, module.name), module));
+        }
+      }
+    }
+    files.push((path.to_string(), layout_rust_source(&f)));
+    for (path, module) in submodules {
+      module.write_files(&path, files)?;
+    }
+    Ok(())
+  }
+
+  /// Writes the `pub use` and `pub mod` lines for the module, with the `pub
+  /// mod` line ending in `end`.
+  fn write_declaration(&self, f: &mut String, end: &str) -> core::fmt::Result {
+    show!(f);
+    if self.glob_use {
+      show!(f, "{cfg}pub use {name}::*;", cfg = self.cfg, name = self.name);
+    }
+    show!(
+      f,
+      "{cfg}{attrs}{vis}mod {name}{end}",
+      cfg = self.cfg,
+      attrs = self.attrs,
+      vis = if self.public { "pub " } else { "" },
+      name = self.name,
+      end = end
+    );
+    Ok(())
+  }
+}
+
+/// The parts of a module that has `code` of its own, and then a private
+/// submodule for each extension with the code in `by_extension`.
+///
+/// The submodules are private so that the same extension's module in `enums`
+/// and `global_commands` don't clash when the crate root re-exports both.
+fn extension_module_parts(
+  code: String, by_extension: BTreeMap<&str, String>, contents: &str,
+  glob_use: bool,
+) -> Vec<ModulePart> {
+  let mut parts = vec![ModulePart::Code(code)];
+  for (extension, extension_code) in by_extension {
+    let docs = format!(
+      "//! The {contents} that only `{extension}` adds.\n  use super::*;\n",
+      contents = contents,
+      extension = extension
+    );
+    parts.push(ModulePart::Module(BindingsModule {
+      name: extension.to_string(),
+      cfg: format!("#[cfg(any(feature=\"{}\"))] ", extension),
+      attrs: "",
+      public: false,
+      glob_use,
+      parts: vec![ModulePart::Code(docs + &extension_code)],
+    }));
+  }
+  parts
+}
+/// Whether a requirement or removal for `profile`, from the feature or
+/// extension named `source`, applies to the `target` profile.
+fn profile_applies(
+  profile: &Option<String>, target: GlProfile, source: &str,
+) -> Result<bool, String> {
+  match profile.as_deref() {
+    None => Ok(true),
+    Some("core") => Ok(target == GlProfile::Core),
+    Some("compatibility") => Ok(target == GlProfile::Compatibility),
+    Some(unknown) => {
+      Err(format!("`{}` needs the unsupported `{}` profile", source, unknown))
+    }
+  }
+}
+
+/// Looks up a command that the feature or extension named `source` requires.
+fn required_command<'r>(
+  index: &RegistryIndex<'r>, source: &str, name: &str,
+) -> Result<&'r GlCommand, String> {
+  index.command(name).ok_or_else(|| {
+    format!("`{}` requires the unknown command `{}`", source, name)
+  })
+}
+
+/// Looks up an enum that the feature or extension named `source` requires.
+fn required_enum<'r>(
+  index: &RegistryIndex<'r>, source: &str, name: &str,
+) -> Result<&'r GlEnum, String> {
+  index
+    .enum_(name)
+    .ok_or_else(|| format!("`{}` requires the unknown enum `{}`", source, name))
+}
+
+impl GlApiSelection {
+  /// This is how you select a specific API level and profile and all that out
+  /// of a GlRegistry.
+  ///
+  /// The maps in the selection are ordered by name, and each item's list of
+  /// extensions is sorted, so the same inputs always give the same output.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\">",
+  ///   "<command><proto>void <name>glFlush</name></proto></command>",
+  ///   "<command><proto>void <name>glFinish</name></proto></command>",
+  ///   "</commands>",
+  ///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\"><require>",
+  ///   "<command name=\"glFlush\"/></require></feature><extensions>",
+  ///   "<extension name=\"GL_EXT_b\" supported=\"gl\"><require>",
+  ///   "<command name=\"glFinish\"/></require></extension>",
+  ///   "<extension name=\"GL_EXT_a\" supported=\"gl\"><require>",
+  ///   "<command name=\"glFinish\"/></require></extension>",
+  ///   "</extensions></registry>",
+  /// ));
+  /// let select = |extensions: &[&str]| {
+  ///   GlApiSelection::new_from_registry_api_extensions(
+  ///     &registry,
+  ///     ApiGroup::Gl,
+  ///     GlVersion::new(1, 0),
+  ///     GlProfile::Core,
+  ///     extensions,
+  ///   )
+  /// };
+  /// let first = select(&["GL_EXT_b", "GL_EXT_a"]);
+  /// let second = select(&["GL_EXT_a", "GL_EXT_b"]);
+  /// assert_eq!(first.to_string(), second.to_string());
+  /// let names: Vec<&str> = first.gl_commands.keys().map(String::as_str).collect();
+  /// assert_eq!(names, ["glFinish", "glFlush"]);
+  /// assert_eq!(
+  ///   first.gl_commands["glFinish"].extensions.as_deref(),
+  ///   Some(&["GL_EXT_a".to_string(), "GL_EXT_b".to_string()][..])
+  /// );
+  /// assert_eq!(first.extension_commands["GL_EXT_a"], ["glFinish"]);
+  /// assert_eq!(
+  ///   first.version_commands,
+  ///   [(GlVersion::new(1, 0), vec!["glFlush".to_string()])]
+  /// );
+  /// ```
+  ///
+  /// ## Panics
+  /// If the selection can't be made, see
+  /// [`Self::try_new_from_registry_api_extensions`].
+  pub fn new_from_registry_api_extensions(
+    reg: &GlRegistry, api: ApiGroup, level: GlVersion,
+    target_profile: GlProfile, extensions: &[&str],
+  ) -> Self {
+    match Self::try_new_from_registry_api_extensions(
+      reg,
+      api,
+      level,
+      target_profile,
+      extensions,
+    ) {
+      Ok(selection) => selection,
+      Err(msg) => panic!("{}", msg),
+    }
+  }
+
+  /// Selects a specific API level and profile out of a GlRegistry, or gives
+  /// an error message if that can't be done.
+  ///
+  /// The `level` has to be a version of the `api` in the registry, each of
+  /// the `extensions` has to be in the registry and support the `api`, and
+  /// every profile that the selected parts of the registry name has to be a
+  /// [`GlProfile`].
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\">",
+  ///   "<command><proto>void <name>glFlush</name></proto></command>",
+  ///   "</commands>",
+  ///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\"><require>",
+  ///   "<command name=\"glFlush\"/></require></feature>",
+  ///   "<feature api=\"gles1\" name=\"GL_VERSION_ES_CM_1_0\" number=\"1.0\">",
+  ///   "<require profile=\"common\"><command name=\"glFlush\"/></require>",
+  ///   "</feature></registry>",
+  /// ));
+  /// let select = |api, level, extensions: &[&str]| {
+  ///   GlApiSelection::try_new_from_registry_api_extensions(
+  ///     &registry,
+  ///     api,
+  ///     level,
+  ///     GlProfile::Core,
+  ///     extensions,
+  ///   )
+  /// };
+  /// assert!(select(ApiGroup::Gl, GlVersion::new(1, 0), &[]).is_ok());
+  /// assert_eq!(
+  ///   select(ApiGroup::Gl, GlVersion::new(9, 9), &[]).unwrap_err(),
+  ///   "the registry has no gl version 9.9"
+  /// );
+  /// assert_eq!(
+  ///   select(ApiGroup::Gl, GlVersion::new(1, 0), &["GL_EXT_x"]).unwrap_err(),
+  ///   "unknown extension `GL_EXT_x`"
+  /// );
+  /// assert_eq!(
+  ///   select(ApiGroup::Gles1, GlVersion::new(1, 0), &[]).unwrap_err(),
+  ///   "`GL_VERSION_ES_CM_1_0` needs the unsupported `common` profile"
+  /// );
+  /// ```
+  pub fn try_new_from_registry_api_extensions(
+    reg: &GlRegistry, api: ApiGroup, level: GlVersion,
+    target_profile: GlProfile, extensions: &[&str],
+  ) -> Result<Self, String> {
+    if !reg.gl_features.iter().any(|f| f.api == api && f.number == level) {
+      return Err(format!(
+        "the registry has no {} version {}",
+        api.supported(),
+        level
+      ));
+    }
+    let index = reg.index();
+    let gl_types: Vec<GlType> = reg.gl_types.clone();
+    let mut gl_enums: BTreeMap<String, GlEnum> = BTreeMap::new();
+    let mut gl_commands: BTreeMap<String, GlCommand> = BTreeMap::new();
+    let mut version_commands: Vec<(GlVersion, Vec<String>)> = Vec::new();
+    //
+    for gl_feature in reg.gl_features.iter() {
+      if gl_feature.api != api || gl_feature.number > level {
+        continue;
+      }
+      let mut added = Vec::new();
+      for GlRequirement { profile, api, adjustment } in
+        gl_feature.required.iter()
+      {
+        if !profile_applies(profile, target_profile, &gl_feature.name)? {
+          continue;
+        }
+        // skip wrong-api requirements
+        if let Some(a) = api {
+          if a != &gl_feature.api {
+            continue;
           }
         }
-        assert!(api.is_none());
         match adjustment {
           ReqRem::Type(_req_type) => (),
-          ReqRem::Command(req_command) => drop(
-            gl_commands.insert(
-              req_command.clone(),
-              reg
-                .gl_commands
-                .iter()
-                .find(|glc| glc.name.as_str() == req_command)
-                .unwrap()
-                .clone(),
-            ),
-          ),
-          ReqRem::Enum(req_enum) => drop(
-            gl_enums.insert(
-              req_enum.clone(),
-              reg
-                .gl_enums
-                .iter()
-                .find(|gle| gle.name.as_str() == req_enum)
-                .unwrap()
-                .clone(),
-            ),
-          ),
+          ReqRem::Command(req_command) => {
+            let gl_command =
+              required_command(&index, &gl_feature.name, req_command)?;
+            if gl_commands
+              .insert(req_command.clone(), gl_command.clone())
+              .is_none()
+            {
+              added.push(req_command.clone());
+            }
+          }
+          ReqRem::Enum(req_enum) => {
+            let gl_enum = required_enum(&index, &gl_feature.name, req_enum)?;
+            gl_enums.insert(req_enum.clone(), gl_enum.clone());
+          }
         }
       }
       //
       for GlRemoval { profile, adjustment } in gl_feature.remove.iter() {
-        if let Some(p) = profile {
-          match p.as_str() {
-            "core" => {
-              if target_profile != GlProfile::Core {
-                continue;
-              }
-            }
-            "compatibility" => {
-              if target_profile != GlProfile::Compatibility {
-                continue;
-              }
-            }
-            unknown => panic!("unknown: {}", unknown),
-          }
+        if !profile_applies(profile, target_profile, &gl_feature.name)? {
+          continue;
         }
         match adjustment {
           ReqRem::Type(_rem_type) => (),
@@ -542,36 +1789,31 @@ impl GlApiSelection {
           ReqRem::Enum(rem_enum) => drop(gl_enums.remove(rem_enum)),
         }
       }
+      version_commands.push((gl_feature.number, added));
     }
     //
     let mut extensions: Vec<String> =
       extensions.iter().copied().map(str::to_string).collect();
     extensions.sort();
+    extensions.dedup();
+    let mut extension_commands: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for extension_name in extensions.iter() {
-      let the_extension = reg
-        .gl_extensions
-        .iter()
-        .find(|gl_ext| gl_ext.name.as_str() == extension_name.as_str())
-        .unwrap();
-      assert!(the_extension.supported.contains(api.supported()), "Requested {extension_name} with api {api:?}, but it is not supported by that API.", extension_name = extension_name, api = api);
+      let the_extension = index
+        .extension(extension_name)
+        .ok_or_else(|| format!("unknown extension `{}`", extension_name))?;
+      if !the_extension.is_supported_by(api) {
+        return Err(format!(
+          "extension `{}` isn't supported by {}",
+          extension_name,
+          api.supported()
+        ));
+      }
       for GlRequirement { profile, api: req_api, adjustment } in
         the_extension.required.iter()
       {
         // skip wrong-profile requirements
-        if let Some(p) = profile {
-          match p.as_str() {
-            "core" => {
-              if target_profile != GlProfile::Core {
-                continue;
-              }
-            }
-            "compatibility" => {
-              if target_profile != GlProfile::Compatibility {
-                continue;
-              }
-            }
-            unknown => panic!("unknown: {}", unknown),
-          }
+        if !profile_applies(profile, target_profile, extension_name)? {
+          continue;
         }
         // skip wrong-api requirements
         if let Some(a) = req_api {
@@ -582,6 +1824,10 @@ impl GlApiSelection {
         match adjustment {
           ReqRem::Type(_req_type) => (),
           ReqRem::Command(req_command) => {
+            extension_commands
+              .entry(extension_name.clone())
+              .or_default()
+              .push(req_command.clone());
             if gl_commands.contains_key(req_command) {
               if let Some(ext) =
                 gl_commands.get_mut(req_command).unwrap().extensions.as_mut()
@@ -589,12 +1835,8 @@ impl GlApiSelection {
                 ext.push(extension_name.clone())
               }
             } else {
-              let mut new_command = reg
-                .gl_commands
-                .iter()
-                .find(|glc| glc.name.as_str() == req_command)
-                .unwrap()
-                .clone();
+              let mut new_command =
+                required_command(&index, extension_name, req_command)?.clone();
               new_command.extensions = Some(vec![extension_name.clone()]);
               gl_commands.insert(req_command.clone(), new_command);
             }
@@ -607,12 +1849,8 @@ impl GlApiSelection {
                 ext.push(extension_name.clone())
               }
             } else {
-              let mut new_enum = reg
-                .gl_enums
-                .iter()
-                .find(|glc| glc.name.as_str() == req_enum)
-                .unwrap()
-                .clone();
+              let mut new_enum =
+                required_enum(&index, extension_name, req_enum)?.clone();
               new_enum.extensions = Some(vec![extension_name.clone()]);
               gl_enums.insert(req_enum.clone(), new_enum);
             }
@@ -620,108 +1858,1779 @@ impl GlApiSelection {
         }
       }
     }
-    // force include all the error enumerations
-    for error_enum_name in [
-      "GL_NO_ERROR",
-      "GL_INVALID_ENUM",
-      "GL_INVALID_VALUE",
-      "GL_INVALID_OPERATION",
-      "GL_INVALID_FRAMEBUFFER_OPERATION",
-      "GL_OUT_OF_MEMORY",
-      "GL_STACK_UNDERFLOW",
-      "GL_STACK_OVERFLOW",
+    // force include all the error enumerations, which the debug code uses.
+    // Their values are fixed, so a registry that leaves one out still works.
+    for (error_enum_name, value) in [
+      ("GL_NO_ERROR", "0"),
+      ("GL_INVALID_ENUM", "0x0500"),
+      ("GL_INVALID_VALUE", "0x0501"),
+      ("GL_INVALID_OPERATION", "0x0502"),
+      ("GL_INVALID_FRAMEBUFFER_OPERATION", "0x0506"),
+      ("GL_OUT_OF_MEMORY", "0x0505"),
+      ("GL_STACK_UNDERFLOW", "0x0504"),
+      ("GL_STACK_OVERFLOW", "0x0503"),
     ]
     .iter()
     .copied()
     {
-      gl_enums.insert(
-        error_enum_name.to_string(),
-        reg
-          .gl_enums
+      let gl_enum = match index.enum_(error_enum_name) {
+        Some(gl_enum) => gl_enum.clone(),
+        None => GlEnum {
+          name: error_enum_name.to_string(),
+          value: value.to_string(),
+          group: None,
+          alias_of: None,
+          api: None,
+          is_bitmask: false,
+          extensions: None,
+          apis: None,
+          core_apis: None,
+        },
+      };
+      gl_enums.insert(error_enum_name.to_string(), gl_enum);
+    }
+    //
+    // an extension can require the same item more than once.
+    for list in gl_commands
+      .values_mut()
+      .filter_map(|gl_command| gl_command.extensions.as_mut())
+      .chain(
+        gl_enums.values_mut().filter_map(|gl_enum| gl_enum.extensions.as_mut()),
+      )
+    {
+      list.sort();
+      list.dedup();
+    }
+    // later versions can remove what earlier ones added.
+    for (_, names) in version_commands.iter_mut() {
+      names.retain(|name| gl_commands.contains_key(name));
+      names.sort();
+      names.dedup();
+    }
+    for names in extension_commands.values_mut() {
+      names.sort();
+      names.dedup();
+    }
+    //
+    let alias_sets = index.command_alias_sets();
+    for gl_command in gl_commands.values_mut() {
+      let load_aliases =
+        match alias_sets.get(index.alias_root(&gl_command.name)) {
+          Some(names) => names
+            .iter()
+            .filter(|name| **name != gl_command.name)
+            .map(|name| name.to_string())
+            .collect(),
+          None => Vec::new(),
+        };
+      gl_command.load_aliases = load_aliases;
+    }
+    //
+    let gl_groups: BTreeMap<String, GlGroup> = reg
+      .gl_groups
+      .values()
+      .map(|gl_group| {
+        let enums = gl_group
+          .enums
           .iter()
-          .find(|gle| gle.name.as_str() == error_enum_name)
-          .unwrap()
-          .clone(),
-      );
+          .filter(|name| gl_enums.contains_key(name.as_str()))
+          .cloned()
+          .collect();
+        (gl_group.name.clone(), GlGroup { name: gl_group.name.clone(), enums })
+      })
+      .collect();
+    //
+    Ok(Self {
+      gl_types,
+      gl_enums,
+      gl_commands,
+      gl_groups,
+      api,
+      version: level,
+      extensions,
+      extension_commands,
+      version_commands,
+      generator: GeneratorOptions::default(),
+      combined_apis: Vec::new(),
+    })
+  }
+
+  /// Combines two selections into one with only the items that are in both.
+  ///
+  /// An item that needs an extension in either selection needs one of those
+  /// extensions in the output. The API, version, and generator options used
+  /// for printing are taken from `a`.
+  ///
+  /// See [`union`](Self::union) for an example.
+  pub fn intersection(a: &Self, b: &Self) -> Self {
+    let mut out = Self::combined_base(a, b);
+    out.gl_enums = intersect_items(&a.gl_enums, &b.gl_enums, |gle| {
+      (&mut gle.apis, &mut gle.extensions, &mut gle.core_apis)
+    });
+    out.gl_commands = intersect_items(&a.gl_commands, &b.gl_commands, |glc| {
+      (&mut glc.apis, &mut glc.extensions, &mut glc.core_apis)
+    });
+    out.gl_groups = combined_groups(a, b, &out.gl_enums);
+    out.extension_commands =
+      combined_extension_commands(a, b, &out.gl_commands);
+    out
+  }
+
+  /// Combines two selections into one with every item from either of them.
+  ///
+  /// Items that aren't in every API of the output are gated on a cargo
+  /// feature for each API that has them (named like `gl` or `gles2`). An item
+  /// that needs an extension in some selections but not others only needs the
+  /// extension along with the features of the APIs that don't have it in
+  /// core.
+  /// The API, version, and generator options used for printing are taken
+  /// from `a`.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\">",
+  ///   "<command><proto>void <name>glFlush</name></proto></command>",
+  ///   "<command><proto>void <name>glPushMatrix</name></proto></command>",
+  ///   "<command><proto>void <name>glDrawThing</name></proto></command>",
+  ///   "</commands>",
+  ///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\"><require>",
+  ///   "<command name=\"glFlush\"/><command name=\"glPushMatrix\"/>",
+  ///   "<command name=\"glDrawThing\"/></require></feature>",
+  ///   "<feature api=\"gles2\" name=\"GL_ES_VERSION_2_0\" number=\"2.0\">",
+  ///   "<require><command name=\"glFlush\"/></require></feature>",
+  ///   "<extensions><extension name=\"GL_EXT_x\" supported=\"gles2\">",
+  ///   "<require><command name=\"glDrawThing\"/></require></extension>",
+  ///   "</extensions></registry>",
+  /// ));
+  /// let select = |api, version, extensions: &[&str]| {
+  ///   GlApiSelection::new_from_registry_api_extensions(
+  ///     &registry,
+  ///     api,
+  ///     version,
+  ///     GlProfile::Compatibility,
+  ///     extensions,
+  ///   )
+  /// };
+  /// let gl = select(ApiGroup::Gl, GlVersion::new(1, 0), &[]);
+  /// let gles = select(ApiGroup::Gles2, GlVersion::new(2, 0), &["GL_EXT_x"]);
+  ///
+  /// let common = GlApiSelection::intersection(&gl, &gles);
+  /// assert!(common.gl_commands.contains_key("glFlush"));
+  /// assert!(!common.gl_commands.contains_key("glPushMatrix"));
+  ///
+  /// let both = GlApiSelection::union(&gl, &gles);
+  /// assert_eq!(both.gl_commands["glFlush"].apis, None);
+  /// assert_eq!(
+  ///   both.gl_commands["glPushMatrix"].apis.as_deref(),
+  ///   Some(&["gl".to_string()][..])
+  /// );
+  /// assert!(both.to_string().contains("#[cfg(any(feature = \"gl\"))]"));
+  ///
+  /// // core in `gl`, but only from an extension in `gles2`.
+  /// let compact = both.to_string().replace(char::is_whitespace, "");
+  /// assert!(compact.contains(concat!(
+  ///   "#[cfg(any(feature=\"gl\",",
+  ///   "all(any(feature=\"gles2\"),any(feature=\"GL_EXT_x\"))",
+  /// )));
+  /// ```
+  pub fn union(a: &Self, b: &Self) -> Self {
+    let mut out = Self::combined_base(a, b);
+    let all_apis = out.api_features();
+    let a_apis = a.api_features();
+    let b_apis = b.api_features();
+    out.gl_enums = union_items(
+      (&a.gl_enums, &a_apis),
+      (&b.gl_enums, &b_apis),
+      &all_apis,
+      |gle| (&mut gle.apis, &mut gle.extensions, &mut gle.core_apis),
+    );
+    out.gl_commands = union_items(
+      (&a.gl_commands, &a_apis),
+      (&b.gl_commands, &b_apis),
+      &all_apis,
+      |glc| (&mut glc.apis, &mut glc.extensions, &mut glc.core_apis),
+    );
+    out.gl_groups = combined_groups(a, b, &out.gl_enums);
+    out.extension_commands =
+      combined_extension_commands(a, b, &out.gl_commands);
+    out
+  }
+
+  /// Gives the selection as JSON, with every command, enum, and group it
+  /// ended up with, along with the options it generates bindings with.
+  ///
+  /// The layout is described at [`GlRegistry::to_json`]. The `gl_enums`,
+  /// `gl_commands`, and `gl_groups` fields are objects keyed by name, and each
+  /// entry of `combined_apis` is an `[api, version]` pair.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let selection = GlApiSelection::default();
+  /// let json = selection.to_json();
+  /// assert!(json.contains(r#""api": "gl""#));
+  /// let loaded = GlApiSelection::from_json(&json).unwrap();
+  /// assert_eq!(loaded.to_string(), selection.to_string());
+  /// ```
+  #[cfg(feature = "json")]
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+
+  /// Rebuilds a selection from the JSON that [`to_json`](Self::to_json)
+  /// gives.
+  #[cfg(feature = "json")]
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    serde_json::from_str(json)
+  }
+
+  /// Everything but the items for a selection that combines `a` and `b`.
+  fn combined_base(a: &Self, b: &Self) -> Self {
+    let mut combined_apis = a.targets();
+    for target in b.targets() {
+      if !combined_apis.contains(&target) {
+        combined_apis.push(target);
+      }
+    }
+    Self {
+      gl_types: a.gl_types.clone(),
+      api: a.api,
+      version: a.version,
+      extensions: merged_lists(&a.extensions, &b.extensions),
+      generator: a.generator,
+      combined_apis,
+      ..Self::default()
+    }
+  }
+
+  /// The APIs that went into this selection.
+  fn targets(&self) -> Vec<(ApiGroup, GlVersion)> {
+    if self.combined_apis.is_empty() {
+      vec![(self.api, self.version)]
+    } else {
+      self.combined_apis.clone()
+    }
+  }
+
+  /// The API features that the output actually uses to gate items, which is
+  /// none of them unless some item isn't in every API.
+  fn api_gate_features(&self) -> Vec<String> {
+    let has_api_gates = self.gl_enums.values().any(|gle| gle.apis.is_some())
+      || self.gl_commands.values().any(|glc| glc.apis.is_some());
+    if has_api_gates {
+      self.api_features()
+    } else {
+      Vec::new()
+    }
+  }
+
+  /// The cargo feature names for the APIs that went into this selection.
+  fn api_features(&self) -> Vec<String> {
+    let mut out: Vec<String> = self
+      .targets()
+      .iter()
+      .map(|(api, _)| String::from(api.supported()))
+      .collect();
+    out.sort();
+    out.dedup();
+    out
+  }
+
+  /// The enum groups that get their own type in the output, along with the GL
+  /// type that each one wraps.
+  ///
+  /// Only groups used by a `GLenum` or `GLbitfield` parameter that have at
+  /// least one enum in this selection are given a type. This is always empty
+  /// if `typed_enum_groups` is off.
+  fn typed_groups(&self) -> BTreeMap<&str, &'static str> {
+    let mut out = BTreeMap::new();
+    if !self.generator.typed_enum_groups {
+      return out;
+    }
+    for gl_command in self.gl_commands.values() {
+      for param in gl_command.params.iter() {
+        let inner_ty = match param.c_type() {
+          "GLenum" => "GLenum",
+          "GLbitfield" => "GLbitfield",
+          _ => continue,
+        };
+        if let Some(group) = param.group() {
+          let has_enums = self
+            .gl_groups
+            .get(group)
+            .map(|gl_group| !gl_group.enums.is_empty())
+            .unwrap_or(false);
+          if has_enums {
+            out.entry(group).or_insert(inner_ty);
+          }
+        }
+      }
+    }
+    out
+  }
+}
+
+/// How wide [`layout_rust_source`] tries to keep lines, indentation included.
+const MAX_LINE_WIDTH: usize = 80;
+
+/// Lays out generated Rust source so that it's readable without running
+/// `rustfmt` on it.
+///
+/// This is nowhere near a real formatter. It only knows enough about Rust's
+/// tokens to handle the code that phosphorus itself generates:
+/// * Each attribute, item, and statement goes on its own line, and so does
+///   the inside of each block.
+/// * Lines are indented two spaces per level of nesting.
+/// * Commas and colons are followed by a space, and `=`, `->`, and `=>` have
+///   a space on both sides.
+/// * A line that's too long has its longest parenthesized or bracketed list
+///   split up, one element per line.
+///
+/// Otherwise the line breaks and spacing of the input are kept as they are.
+fn layout_rust_source(src: &str) -> String {
+  let tokens = lex_rust_source(src);
+
+  // Figure out where the lines start.
+  let mut lines: Vec<(core::ops::Range<usize>, bool)> = Vec::new();
+  let mut nesting: Vec<Nesting> = Vec::new();
+  let mut line_start = 0;
+  let mut break_next = false;
+  let mut closed_block = false;
+  for (i, token) in tokens.iter().enumerate() {
+    let at_item_level = matches!(nesting.last(), None | Some(Nesting::Block));
+    let closes_block =
+      token.kind == TokenKind::Close && nesting.last() == Some(&Nesting::Block);
+    let new_line = token.newlines_before > 0
+      || closes_block
+      || (token.kind != TokenKind::Comment
+        && (break_next
+          || (closed_block
+            && token.kind != TokenKind::Close
+            && !matches!(token.text, "," | ";" | "." | "?" | "else"))));
+    if new_line && i > line_start {
+      let blank_before = tokens[line_start].newlines_before > 1;
+      lines.push((line_start..i, blank_before));
+      line_start = i;
+    }
+    break_next = false;
+    closed_block = false;
+    let prev_text = |back: usize| {
+      i.checked_sub(back).map(|prev| tokens[prev].text).unwrap_or("")
+    };
+    match token.kind {
+      TokenKind::Open => {
+        let n = if token.text == "{" {
+          let immediately_closed =
+            tokens.get(i + 1).map(|next| next.text == "}").unwrap_or(false);
+          if at_item_level && prev_text(1) != "::" && !immediately_closed {
+            break_next = true;
+            Nesting::Block
+          } else {
+            Nesting::InlineBraces
+          }
+        } else if token.text == "["
+          && (prev_text(1) == "#"
+            || (prev_text(1) == "!" && prev_text(2) == "#"))
+        {
+          Nesting::Attribute
+        } else {
+          Nesting::List
+        };
+        nesting.push(n);
+      }
+      TokenKind::Close => match nesting.pop() {
+        Some(Nesting::Block) => closed_block = true,
+        Some(Nesting::Attribute) => {
+          break_next = matches!(nesting.last(), None | Some(Nesting::Block));
+        }
+        _ => (),
+      },
+      TokenKind::Punct if token.text == ";" => break_next = at_item_level,
+      _ => (),
+    }
+  }
+  if line_start < tokens.len() {
+    let blank_before = tokens[line_start].newlines_before > 1;
+    lines.push((line_start..tokens.len(), blank_before));
+  }
+
+  // Then print them out with the right indentation.
+  let mut out = String::with_capacity(src.len());
+  let mut open_levels: Vec<usize> = Vec::new();
+  let mut after_open = true;
+  let mut in_where_clause = false;
+  for (range, blank_before) in lines {
+    let line = &tokens[range];
+    let starts_with_close = line[0].kind == TokenKind::Close;
+    let mut level = if starts_with_close {
+      open_levels.last().copied().unwrap_or(0)
+    } else {
+      open_levels.last().map(|level| level + 1).unwrap_or(0)
+    };
+    if in_where_clause {
+      // the bounds are indented past the `where`, until the body starts
+      if line[0].text == "{" {
+        in_where_clause = false;
+      } else {
+        level += 1;
+      }
+    }
+    if blank_before && !after_open && !starts_with_close {
+      out.push('\n');
+    }
+    write_wrapped_line(&mut out, level, line, "");
+    for token in line {
+      match token.kind {
+        TokenKind::Open => open_levels.push(level),
+        TokenKind::Close => {
+          open_levels.pop();
+        }
+        _ => (),
+      }
+    }
+    after_open = line[line.len() - 1].kind == TokenKind::Open;
+    in_where_clause |= line.len() == 1 && line[0].text == "where";
+  }
+  out
+}
+
+/// What sort of delimiter [`layout_rust_source`] is inside of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Nesting {
+  /// Parens, or brackets other than an attribute's.
+  List,
+  /// The brackets of an attribute.
+  Attribute,
+  /// Braces that get their insides put on separate lines.
+  Block,
+  /// Braces that stay how they are, such as those of `use core::{a, b};`.
+  InlineBraces,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+  Word,
+  Literal,
+  Comment,
+  Open,
+  Close,
+  Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SourceToken<'s> {
+  text: &'s str,
+  kind: TokenKind,
+  /// If there was any whitespace between this and the previous token.
+  space_before: bool,
+  /// How many newlines were between this and the previous token.
+  newlines_before: usize,
+}
+
+/// Splits Rust source into tokens, close enough for [`layout_rust_source`].
+fn lex_rust_source(src: &str) -> Vec<SourceToken<'_>> {
+  const LONG_PUNCTS: &[&str] = &[
+    "..=", "...", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=",
+    "-=", "*=", "/=", "%=", "^=", "&=", "|=", "..",
+  ];
+  let is_word_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+  let bytes = src.as_bytes();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  let mut space_before = false;
+  let mut newlines_before = 0;
+  while i < bytes.len() {
+    let b = bytes[i];
+    if b.is_ascii_whitespace() {
+      space_before = true;
+      if b == b'\n' {
+        newlines_before += 1;
+      }
+      i += 1;
+      continue;
+    }
+    let rest = &src[i..];
+    let (len, kind) = if rest.starts_with("//") {
+      (rest.find('\n').unwrap_or(rest.len()), TokenKind::Comment)
+    } else if rest.starts_with("/*") {
+      let len = rest.find("*/").map(|end| end + 2).unwrap_or(rest.len());
+      (len, TokenKind::Comment)
+    } else if let Some(len) = string_literal_len(rest) {
+      (len, TokenKind::Literal)
+    } else if b == b'\'' {
+      let mut chars = rest[1..].chars();
+      match (chars.next(), chars.next()) {
+        (Some('\\'), _) | (Some(_), Some('\'')) => {
+          // skip past an escaped character before looking for the end quote
+          let skip = if rest[1..].starts_with('\\') { 3 } else { 1 };
+          let len = rest
+            .get(skip..)
+            .and_then(|after| after.find('\''))
+            .map(|end| skip + end + 1)
+            .unwrap_or(rest.len());
+          (len, TokenKind::Literal)
+        }
+        // a lifetime
+        _ => {
+          let len =
+            1 + rest[1..].bytes().take_while(|&b| is_word_byte(b)).count();
+          (len, TokenKind::Word)
+        }
+      }
+    } else if b.is_ascii_digit() {
+      let mut len = 0;
+      while let Some(&b) = bytes.get(i + len) {
+        let decimal_point = b == b'.'
+          && bytes.get(i + len + 1).map(u8::is_ascii_digit).unwrap_or(false);
+        if is_word_byte(b) || decimal_point {
+          len += 1;
+        } else {
+          break;
+        }
+      }
+      (len, TokenKind::Literal)
+    } else if is_word_byte(b)
+      || (b == b'$'
+        && bytes.get(i + 1).map(|&b| is_word_byte(b)).unwrap_or(false))
+    {
+      let len = 1 + rest[1..].bytes().take_while(|&b| is_word_byte(b)).count();
+      (len, TokenKind::Word)
+    } else if b"([{".contains(&b) {
+      (1, TokenKind::Open)
+    } else if b")]}".contains(&b) {
+      (1, TokenKind::Close)
+    } else {
+      let len = match LONG_PUNCTS.iter().find(|punct| rest.starts_with(*punct))
+      {
+        Some(punct) => punct.len(),
+        None => rest.chars().next().map(char::len_utf8).unwrap_or(1),
+      };
+      (len, TokenKind::Punct)
+    };
+    tokens.push(SourceToken {
+      text: &rest[..len],
+      kind,
+      space_before,
+      newlines_before,
+    });
+    i += len;
+    space_before = false;
+    newlines_before = 0;
+  }
+  tokens
+}
+
+/// The length of the string literal (of any sort) at the start of `rest`, if
+/// there is one.
+fn string_literal_len(rest: &str) -> Option<usize> {
+  let prefix = rest.bytes().take_while(|&b| b == b'b' || b == b'r').count();
+  if !matches!(&rest[..prefix], "" | "b" | "r" | "br") {
+    return None;
+  }
+  let raw = rest[..prefix].ends_with('r');
+  let hashes = if raw {
+    rest[prefix..].bytes().take_while(|&b| b == b'#').count()
+  } else {
+    0
+  };
+  let open_quote = prefix + hashes;
+  if rest.as_bytes().get(open_quote) != Some(&b'"') {
+    return None;
+  }
+  if raw {
+    let close = format!("\"{}", "#".repeat(hashes));
+    return Some(
+      rest[open_quote + 1..]
+        .find(&close)
+        .map(|end| open_quote + 1 + end + close.len())
+        .unwrap_or(rest.len()),
+    );
+  }
+  let mut escaped = false;
+  for (i, b) in rest.bytes().enumerate().skip(open_quote + 1) {
+    if escaped {
+      escaped = false;
+    } else if b == b'\\' {
+      escaped = true;
+    } else if b == b'"' {
+      return Some(i + 1);
+    }
+  }
+  Some(rest.len())
+}
+
+/// Writes out one line of tokens followed by `suffix`. If that's too wide it
+/// splits up the line's longest parenthesized or bracketed list and tries
+/// again with each of the pieces.
+///
+/// Lists of plain names, such as `(a, b, c)`, are packed onto as few lines
+/// as they fit on instead of getting a line per element.
+fn write_wrapped_line(
+  out: &mut String, level: usize, line: &[SourceToken<'_>], suffix: &str,
+) {
+  let text = tokens_to_text(line);
+  let width = 2 * level + text.chars().count() + suffix.len();
+  if width > MAX_LINE_WIDTH && !text.contains('\n') {
+    if let Some((open, close)) = longest_splittable_list(line) {
+      let mut elements = Vec::new();
+      let mut element_start = open + 1;
+      let mut depth = 0_usize;
+      for i in open + 1..=close {
+        match line[i].kind {
+          TokenKind::Open => depth += 1,
+          TokenKind::Close if depth > 0 => depth -= 1,
+          _ => (),
+        }
+        if (depth == 0 && line[i].text == ",") || i == close {
+          if i > element_start {
+            elements.push(&line[element_start..i]);
+          }
+          element_start = i + 1;
+        }
+      }
+      write_wrapped_line(out, level, &line[..=open], "");
+      let all_words = elements.iter().all(|element| {
+        element.len() == 1 && element[0].kind == TokenKind::Word
+      });
+      if all_words {
+        let mut packed = String::new();
+        for element in elements {
+          let extra = if packed.is_empty() { 0 } else { 1 };
+          let packed_width = 2 * (level + 1) + packed.len() + extra;
+          if packed_width + element[0].text.len() + 1 > MAX_LINE_WIDTH
+            && !packed.is_empty()
+          {
+            write_wrapped_line(out, level + 1, &[], &packed);
+            packed.clear();
+          }
+          if !packed.is_empty() {
+            packed.push(' ');
+          }
+          packed.push_str(element[0].text);
+          packed.push(',');
+        }
+        write_wrapped_line(out, level + 1, &[], &packed);
+      } else {
+        for element in elements {
+          write_wrapped_line(out, level + 1, element, ",");
+        }
+      }
+      write_wrapped_line(out, level, &line[close..], suffix);
+      return;
+    }
+  }
+  for _ in 0..level {
+    out.push_str("  ");
+  }
+  out.push_str(&text);
+  out.push_str(suffix);
+  out.push('\n');
+}
+
+/// Finds the longest `( ... )` or `[ ... ]` list in a line that can be split
+/// at its commas, giving the index of each paren or bracket. Lists inside of
+/// other lists are only picked when the outer list can't be split, such as
+/// the `any(..)` of `#[cfg(any(..))]`.
+///
+/// Lists with a `<`, `>`, or `|` at their top level are left alone, along
+/// with everything inside of them, since there's no telling if their commas
+/// are between generic args or closure params. A list without a comma is only
+/// split if it looks like the args of a call, since a trailing comma would
+/// turn something like `pub(crate)`, `(a + b)`, or `x[i]` into something
+/// else.
+fn longest_splittable_list(line: &[SourceToken<'_>]) -> Option<(usize, usize)> {
+  /// A list that's open at some point of the line.
+  struct OpenList {
+    open: usize,
+    /// If this is a `( ... )` or `[ ... ]` list at all.
+    is_list: bool,
+    /// If there's no `<`, `>`, or `|` at its top level.
+    splittable: bool,
+    /// If a trailing comma after the last element is fine.
+    can_trail: bool,
+    /// The longest splittable list within this one.
+    best_inside: Option<(usize, usize, usize)>,
+  }
+  let longest = |a: Option<(usize, usize, usize)>, b| match (a, b) {
+    (Some((_, _, a_len)), Some((_, _, b_len))) if b_len > a_len => b,
+    (None, b) => b,
+    (a, _) => a,
+  };
+  let mut best: Option<(usize, usize, usize)> = None;
+  let mut open_lists: Vec<OpenList> = Vec::new();
+  for (i, token) in line.iter().enumerate() {
+    match token.kind {
+      TokenKind::Open => {
+        // a word before a `(` makes it a call, except for keywords and
+        // lifetimes, where it's a parenthesized type or expression instead.
+        let is_call = token.text == "("
+          && i > 0
+          && line[i - 1].kind == TokenKind::Word
+          && !line[i - 1].text.starts_with('\'')
+          && !matches!(
+            line[i - 1].text,
+            "pub"
+              | "if"
+              | "while"
+              | "match"
+              | "return"
+              | "in"
+              | "as"
+              | "mut"
+              | "const"
+              | "dyn"
+          );
+        open_lists.push(OpenList {
+          open: i,
+          is_list: matches!(token.text, "(" | "["),
+          splittable: true,
+          can_trail: is_call,
+          best_inside: None,
+        });
+      }
+      TokenKind::Close => {
+        // a close that was opened on some earlier line doesn't matter.
+        if let Some(list) = open_lists.pop() {
+          let found = if !list.splittable {
+            None
+          } else if list.is_list && list.can_trail && i > list.open + 1 {
+            let len: usize =
+              line[list.open..=i].iter().map(|token| token.text.len()).sum();
+            Some((list.open, i, len))
+          } else {
+            list.best_inside
+          };
+          match open_lists.last_mut() {
+            Some(outer) => {
+              outer.best_inside = longest(outer.best_inside, found)
+            }
+            None => best = longest(best, found),
+          }
+        }
+      }
+      TokenKind::Punct if matches!(token.text, "<" | ">" | "|") => {
+        if let Some(list) = open_lists.last_mut() {
+          list.splittable &= !list.is_list;
+        }
+      }
+      TokenKind::Punct if token.text == "," => {
+        if let Some(list) = open_lists.last_mut() {
+          list.can_trail = true;
+        }
+      }
+      _ => (),
+    }
+  }
+  best.map(|(open, close, _)| (open, close))
+}
+
+/// Joins up a line's tokens, normalizing some of the spacing between them.
+fn tokens_to_text(line: &[SourceToken<'_>]) -> String {
+  let mut text = String::new();
+  for (i, token) in line.iter().enumerate() {
+    if i > 0 {
+      let prev = line[i - 1];
+      let space = match (prev.text, token.text) {
+        (_, "," | ";" | ":") => false,
+        ("(" | "[", _) => false,
+        (",", _) => token.kind != TokenKind::Close,
+        // keep macro fragments like `$arg:tt` together
+        (":", _) => !(i >= 2 && line[i - 2].text.starts_with('$')),
+        ("=" | "->" | "=>", _) | (_, "=" | "->" | "=>") => true,
+        (_, ")" | "]") => false,
+        (_, "{") if prev.kind != TokenKind::Punct || prev.text == ">" => {
+          prev.kind != TokenKind::Open
+        }
+        _ => token.space_before,
+      };
+      if space {
+        text.push(' ');
+      }
+    }
+    if token.kind == TokenKind::Comment {
+      text.push_str(token.text.trim_end());
+    } else {
+      text.push_str(token.text);
+    }
+  }
+  text
+}
+
+/// The `Cargo.toml` for a crate made out of the bindings that a
+/// [`GlApiSelection`] generates.
+///
+/// It declares all the cargo features that the bindings use: the standard
+/// ones, one per extension, and one per API when a combined selection gates
+/// items by API. Just like with the bindings, use the `Display` impl to print
+/// it out.
+/// ```rust
+/// # use phosphorus::*;
+/// let mut selection = GlApiSelection::default();
+/// selection.extensions.push(String::from("GL_KHR_debug"));
+/// let manifest = CrateManifest::for_selection(&selection, "my_gl");
+/// let toml = manifest.to_string();
+/// assert!(toml.contains("name = \"my_gl\""));
+/// assert!(toml.contains("\nGL_KHR_debug = []\n"));
+/// assert!(toml.contains("\nlazy_loader = []\n"));
+/// assert!(toml.contains("\nlog = { version = \"0.4\", optional = true }\n"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateManifest {
+  /// The package name.
+  pub name: String,
+  /// The package version.
+  pub version: String,
+  /// The features that are on by default.
+  pub default_features: Vec<String>,
+  /// The features from the selection, one per extension (and one per API for
+  /// a combined selection that needs them).
+  pub selection_features: Vec<String>,
+}
+impl CrateManifest {
+  /// The features that every generated crate supports.
+  ///
+  /// `log`, `chlorine`, and `bytemuck` are also features, but those come from
+  /// the optional dependencies of the same name.
+  pub const STANDARD_FEATURES: &'static [&'static str] = &[
+    "global_loader",
+    "struct_loader",
+    "lazy_loader",
+    "debug_trace_calls",
+    "debug_automatic_glGetError",
+    "inline",
+    "inline_always",
+  ];
+
+  /// Makes the manifest that goes with the bindings of a selection.
+  ///
+  /// The version starts as `0.1.0`, and both loaders are on by default.
+  pub fn for_selection(selection: &GlApiSelection, name: &str) -> Self {
+    Self {
+      name: String::from(name),
+      version: String::from("0.1.0"),
+      default_features: vec![
+        String::from("global_loader"),
+        String::from("struct_loader"),
+      ],
+      selection_features: merged_lists(
+        &selection.api_gate_features(),
+        &selection.extensions,
+      ),
+    }
+  }
+}
+impl core::fmt::Display for CrateManifest {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    show!(f, "# Generated by phosphorus-{}", env!("CARGO_PKG_VERSION"));
+    show!(f);
+    show!(f, "[package]");
+    show!(f, "name = {:?}", self.name);
+    show!(f, "version = {:?}", self.version);
+    show!(f, "edition = \"2018\"");
+    show!(f);
+    show!(f, "[package.metadata.docs.rs]");
+    show!(f, "all-features = true");
+    show!(f, "rustdoc-args = [\"--cfg\", \"docs_rs\"]");
+    show!(f);
+    show!(f, "[lints.rust]");
+    show!(
+      f,
+      "unexpected_cfgs = {{ level = \"warn\", check-cfg = ['cfg(docs_rs)'] }}"
+    );
+    show!(f);
+    show!(f, "[dependencies]");
+    show!(f, "chlorine = {{ version = \"1.0.5\", optional = true }}");
+    show!(f, "log = {{ version = \"0.4\", optional = true }}");
+    show!(f, "bytemuck = {{ version = \"1\", optional = true }}");
+    show!(f);
+    show!(f, "[features]");
+    show!(f, "default = {:?}", self.default_features);
+    for feature in Self::STANDARD_FEATURES {
+      show!(f, "{} = []", feature);
+    }
+    for feature in self.selection_features.iter() {
+      show!(f, "{} = []", feature);
+    }
+    Ok(())
+  }
+}
+
+/// The access to an item's `apis` and `extensions` lists, which is what
+/// combining selections needs to change.
+type GatesOf<T> = fn(
+  &mut T,
+) -> (
+  &mut Option<Vec<String>>,
+  &mut Option<Vec<String>>,
+  &mut Option<Vec<String>>,
+);
+
+/// The commands that each extension of `a` or `b` requires, out of the
+/// `gl_commands` that a selection combining them ended up with.
+fn combined_extension_commands(
+  a: &GlApiSelection, b: &GlApiSelection,
+  gl_commands: &BTreeMap<String, GlCommand>,
+) -> BTreeMap<String, Vec<String>> {
+  let mut out: BTreeMap<String, Vec<String>> = BTreeMap::new();
+  for (extension, names) in
+    a.extension_commands.iter().chain(b.extension_commands.iter())
+  {
+    let list = out.entry(extension.clone()).or_default();
+    *list = merged_lists(list, names);
+    list.retain(|name| gl_commands.contains_key(name));
+  }
+  out
+}
+
+/// Sorts the two lists together, without duplicates.
+fn merged_lists(a: &[String], b: &[String]) -> Vec<String> {
+  let mut out: Vec<String> = a.iter().chain(b.iter()).cloned().collect();
+  out.sort();
+  out.dedup();
+  out
+}
+
+/// The items in both `a` and `b`, gated on everything either one is gated on.
+fn intersect_items<T: Clone>(
+  a: &BTreeMap<String, T>, b: &BTreeMap<String, T>, gates_of: GatesOf<T>,
+) -> BTreeMap<String, T> {
+  let mut out = BTreeMap::new();
+  for (name, item) in a.iter() {
+    if let Some(other) = b.get(name) {
+      let mut item = item.clone();
+      let mut other = other.clone();
+      let (apis, extensions, core_apis) = gates_of(&mut item);
+      let (other_apis, other_extensions, _) = gates_of(&mut other);
+      // any extension that either one needs is needed by every API.
+      *core_apis = None;
+      for (gate, other_gate) in
+        [(apis, other_apis), (extensions, other_extensions)]
+      {
+        *gate = match (gate.take(), other_gate.take()) {
+          (None, None) => None,
+          (Some(list), None) | (None, Some(list)) => Some(list),
+          (Some(x), Some(y)) => Some(merged_lists(&x, &y)),
+        };
+      }
+      out.insert(name.clone(), item);
+    }
+  }
+  out
+}
+
+/// The items in either `a` or `b`, each paired with the API features of its
+/// selection.
+///
+/// An item that is in every API of `all_apis` isn't gated on any of them,
+/// unless some of those APIs need an extension for it.
+fn union_items<T: Clone>(
+  a: (&BTreeMap<String, T>, &[String]), b: (&BTreeMap<String, T>, &[String]),
+  all_apis: &[String], gates_of: GatesOf<T>,
+) -> BTreeMap<String, T> {
+  let mut out: BTreeMap<String, T> = BTreeMap::new();
+  for (items, selection_apis) in [a, b] {
+    for (name, item) in items.iter() {
+      let mut item = item.clone();
+      let (apis, extensions, core_apis) = gates_of(&mut item);
+      let item_apis = apis.take().unwrap_or_else(|| selection_apis.to_vec());
+      match out.get_mut(name) {
+        Some(existing) => {
+          let (existing_apis, existing_extensions, existing_core_apis) =
+            gates_of(existing);
+          // the APIs that have the item without an extension.
+          let core_of =
+            |apis: &[String],
+             extensions: &Option<Vec<String>>,
+             core_apis: &Option<Vec<String>>| {
+              match extensions {
+                None => apis.to_vec(),
+                Some(_) => core_apis.clone().unwrap_or_default(),
+              }
+            };
+          let both_core_apis = merged_lists(
+            &core_of(
+              existing_apis.as_deref().unwrap_or(&[]),
+              existing_extensions,
+              existing_core_apis,
+            ),
+            &core_of(&item_apis, extensions, core_apis),
+          );
+          let both_apis =
+            merged_lists(existing_apis.as_deref().unwrap_or(&[]), &item_apis);
+          *existing_extensions =
+            match (existing_extensions.take(), extensions.take()) {
+              (None, None) => None,
+              (Some(list), None) | (None, Some(list)) => Some(list),
+              (Some(x), Some(y)) => Some(merged_lists(&x, &y)),
+            };
+          *existing_core_apis = match existing_extensions {
+            Some(_) if !both_core_apis.is_empty() => Some(both_core_apis),
+            _ => None,
+          };
+          *existing_apis = Some(both_apis);
+        }
+        None => {
+          *apis = Some(item_apis);
+          out.insert(name.clone(), item);
+        }
+      }
+    }
+  }
+  for item in out.values_mut() {
+    let (apis, _, core_apis) = gates_of(item);
+    if apis.as_deref() == Some(all_apis) && core_apis.is_none() {
+      *apis = None;
+    }
+  }
+  out
+}
+
+/// The enum groups of both selections, narrowed down to the enums given.
+fn combined_groups(
+  a: &GlApiSelection, b: &GlApiSelection, gl_enums: &BTreeMap<String, GlEnum>,
+) -> BTreeMap<String, GlGroup> {
+  let mut out: BTreeMap<String, GlGroup> = BTreeMap::new();
+  for gl_group in a.gl_groups.values().chain(b.gl_groups.values()) {
+    let group = out.entry(gl_group.name.clone()).or_insert_with(|| GlGroup {
+      name: gl_group.name.clone(),
+      ..GlGroup::default()
+    });
+    let enums = gl_group
+      .enums
+      .iter()
+      .filter(|name| gl_enums.contains_key(name.as_str()))
+      .cloned()
+      .collect::<Vec<_>>();
+    group.enums = merged_lists(&group.enums, &enums);
+  }
+  out
+}
+
+/// Makes the `cfg` attributes for an item that's only included because of the
+/// APIs or extensions listed.
+///
+/// Items that are in the core API (no extensions) of every API don't need any
+/// attributes. Items with `core_apis` are in the output for those APIs, or for
+/// the rest of `apis` with one of the `extensions`.
+fn item_cfg(
+  apis: Option<&Vec<String>>, extensions: Option<&Vec<String>>,
+  core_apis: Option<&Vec<String>>,
+) -> String {
+  match item_cfg_predicate(apis, extensions, core_apis) {
+    Some(predicate) => format!(
+      "#[cfg({predicate})]#[cfg_attr(docs_rs, doc(cfg({predicate})))]",
+      predicate = predicate
+    ),
+    None => String::new(),
+  }
+}
+
+/// Like [`item_cfg`], but only a plain `cfg` attribute, which is what
+/// statements need.
+fn item_cfg_statement(
+  apis: Option<&Vec<String>>, extensions: Option<&Vec<String>>,
+  core_apis: Option<&Vec<String>>,
+) -> String {
+  match item_cfg_predicate(apis, extensions, core_apis) {
+    Some(predicate) => format!("#[cfg({})]", predicate),
+    None => String::new(),
+  }
+}
+
+fn item_cfg_predicate(
+  apis: Option<&Vec<String>>, extensions: Option<&Vec<String>>,
+  core_apis: Option<&Vec<String>>,
+) -> Option<String> {
+  if let Some(core_apis) = core_apis {
+    let other_apis: Vec<String> = apis
+      .into_iter()
+      .flatten()
+      .filter(|api| !core_apis.contains(api))
+      .cloned()
+      .collect();
+    let mut predicate = String::from("any(");
+    for l in core_apis.iter() {
+      let _cant_fail = write!(predicate, "feature=\"{l}\",", l = l);
+    }
+    if let Some(gated) = item_cfg_predicate(Some(&other_apis), extensions, None)
+    {
+      predicate.push_str(&gated);
+    }
+    predicate.push(')');
+    return Some(predicate);
+  }
+  let any_feature = |list: &Vec<String>| {
+    let mut predicate = String::from("any(");
+    for (i, l) in list.iter().enumerate() {
+      if i != 0 {
+        predicate.push(',');
+      }
+      let _cant_fail = write!(predicate, "feature=\"{l}\"", l = l);
+    }
+    predicate.push(')');
+    predicate
+  };
+  match (apis.map(any_feature), extensions.map(any_feature)) {
+    (None, None) => None,
+    (Some(predicate), None) | (None, Some(predicate)) => Some(predicate),
+    (Some(apis), Some(extensions)) => {
+      Some(format!("all({},{})", apis, extensions))
+    }
+  }
+}
+
+/// Prints the newtype for an enum group.
+///
+/// Groups that wrap a `GLbitfield` are bit flags, so they also get the bitwise
+/// operators and a few helper methods.
+struct GlGroupDisplayer<'a> {
+  gl_group: &'a GlGroup,
+  inner_ty: &'a str,
+  gl_enums: &'a BTreeMap<String, GlEnum>,
+}
+impl core::fmt::Display for GlGroupDisplayer<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let name = &self.gl_group.name;
+    let inner_ty = self.inner_ty;
+    let is_bitmask = inner_ty == "GLbitfield";
+    // a `u64` value can't go in the group's type.
+    let members: Vec<&GlEnum> = self
+      .gl_group
+      .enums
+      .iter()
+      .map(|enum_name| &self.gl_enums[enum_name])
+      .filter(|gl_enum| gl_enum.value != "0xFFFFFFFFFFFFFFFF")
+      .collect();
+    show!(
+      f,
+      "/// Group `{name}`, as a `{inner_ty}`.{bitmask_doc}
+#[derive({debug}Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct {name}(pub {inner_ty});
+impl From<{inner_ty}> for {name} {{
+  #[inline]
+  fn from(raw: {inner_ty}) -> Self {{
+    Self(raw)
+  }}
+}}
+impl From<{name}> for {inner_ty} {{
+  #[inline]
+  fn from(value: {name}) -> Self {{
+    value.0
+  }}
+}}
+impl {name} {{",
+      name = name,
+      inner_ty = inner_ty,
+      bitmask_doc = if is_bitmask {
+        "\n///\n/// These are bit flags, so they can be combined with `|` and the other bitwise operators."
+      } else {
+        ""
+      },
+      debug = if is_bitmask { "" } else { "Debug, " },
+    );
+    for gl_enum in members.iter() {
+      show!(
+        f,
+        "  /// [`{enum_name}`]
+  {extensions}pub const {const_name}: Self = Self({enum_name});",
+        enum_name = gl_enum.name,
+        const_name = group_const_name(&gl_enum.name),
+        extensions = item_cfg(
+          gl_enum.apis.as_ref(),
+          gl_enum.extensions.as_ref(),
+          gl_enum.core_apis.as_ref()
+        ),
+      );
+    }
+    if !is_bitmask {
+      return write!(f, "}}");
+    }
+    let mut all_bits = String::new();
+    let mut debug_flags = String::new();
+    for gl_enum in members.iter() {
+      let cfg = item_cfg_statement(
+        gl_enum.apis.as_ref(),
+        gl_enum.extensions.as_ref(),
+        gl_enum.core_apis.as_ref(),
+      );
+      show!(
+        all_bits,
+        "    {cfg}{{ bits |= {enum_name}; }}",
+        cfg = cfg,
+        enum_name = gl_enum.name
+      );
+      show!(
+        debug_flags,
+        "    {cfg}{{ flag(f, \"{const_name}\", {enum_name})?; }}",
+        cfg = cfg,
+        const_name = group_const_name(&gl_enum.name),
+        enum_name = gl_enum.name
+      );
+    }
+    show!(
+      f,
+      "  /// No flags set.
+  #[inline]
+  pub const fn empty() -> Self {{
+    Self(0)
+  }}
+  /// Every flag of the group set.
+  #[inline]
+  pub const fn all() -> Self {{
+    // every flag might be cfg'd out.
+    #[allow(unused_mut)]
+    let mut bits = 0;
+{all_bits}    Self(bits)
+  }}
+  /// If no flags are set.
+  #[inline]
+  pub const fn is_empty(self) -> bool {{
+    self.0 == 0
+  }}
+  /// If all the flags set in `other` are also set in `self`.
+  #[inline]
+  pub const fn contains(self, other: Self) -> bool {{
+    (self.0 & other.0) == other.0
+  }}
+}}
+impl core::ops::BitOr for {name} {{
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {{
+    Self(self.0 | rhs.0)
+  }}
+}}
+impl core::ops::BitOrAssign for {name} {{
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {{
+    self.0 |= rhs.0
+  }}
+}}
+impl core::ops::BitAnd for {name} {{
+  type Output = Self;
+  #[inline]
+  fn bitand(self, rhs: Self) -> Self {{
+    Self(self.0 & rhs.0)
+  }}
+}}
+impl core::ops::BitAndAssign for {name} {{
+  #[inline]
+  fn bitand_assign(&mut self, rhs: Self) {{
+    self.0 &= rhs.0
+  }}
+}}
+impl core::ops::BitXor for {name} {{
+  type Output = Self;
+  #[inline]
+  fn bitxor(self, rhs: Self) -> Self {{
+    Self(self.0 ^ rhs.0)
+  }}
+}}
+impl core::ops::BitXorAssign for {name} {{
+  #[inline]
+  fn bitxor_assign(&mut self, rhs: Self) {{
+    self.0 ^= rhs.0
+  }}
+}}
+impl core::ops::Not for {name} {{
+  type Output = Self;
+  #[inline]
+  fn not(self) -> Self {{
+    Self(!self.0)
+  }}
+}}
+impl core::fmt::Debug for {name} {{
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{
+    write!(f, \"{name}(\")?;
+    let mut remaining = self.0;
+    let mut first = true;
+    #[allow(unused_mut, unused_variables)]
+    let mut flag = |f: &mut core::fmt::Formatter, flag_name: &str, bits: {inner_ty}| -> core::fmt::Result {{
+      if bits != 0 && (self.0 & bits) == bits && (remaining & bits) != 0 {{
+        if !first {{
+          f.write_str(\" | \")?;
+        }}
+        first = false;
+        remaining &= !bits;
+        f.write_str(flag_name)?;
+      }}
+      Ok(())
+    }};
+{debug_flags}    if remaining != 0 || first {{
+      if !first {{
+        f.write_str(\" | \")?;
+      }}
+      write!(f, \"{{:#X}}\", remaining)?;
+    }}
+    write!(f, \")\")
+  }}
+}}",
+      name = name,
+      inner_ty = inner_ty,
+      all_bits = all_bits,
+      debug_flags = debug_flags,
+    );
+    Ok(())
+  }
+}
+
+/// The name of an enum when it's an associated constant of a group type.
+///
+/// We strip the `GL_` prefix, unless that would leave a name that starts with
+/// a digit.
+fn group_const_name(enum_name: &str) -> &str {
+  match enum_name.strip_prefix("GL_") {
+    Some(short) if !short.starts_with(|c: char| c.is_ascii_digit()) => short,
+    _ => enum_name,
+  }
+}
+
+/// An error from trying to parse `gl.xml` into a [`GlRegistry`].
+///
+/// This names the tag and (when relevant) the attribute that couldn't be
+/// understood, along with where in the source text the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryError {
+  /// The tag being processed when the problem was found.
+  pub element: String,
+  /// The attribute key, if the problem was with a particular attribute.
+  pub attribute: Option<String>,
+  /// A description of the problem.
+  pub message: String,
+  position: ErrorPosition,
+}
+/// Where a [`RegistryError`] happened.
+///
+/// Deep within the parser we don't have the source text on hand, so at first
+/// we can only keep the address of the problem (or note that the input ran
+/// out). The position within the source is worked out once the error gets
+/// back up to where the source text is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorPosition {
+  Address(usize),
+  EndOfInput,
+  Located { offset: usize, line: usize },
+}
+impl core::fmt::Display for RegistryError {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if let ErrorPosition::Located { offset, line } = self.position {
+      write!(f, "line {}, byte {}: ", line, offset)?;
+    }
+    write!(f, "<{}>", self.element)?;
+    if let Some(attribute) = self.attribute.as_ref() {
+      write!(f, " attribute `{}`", attribute)?;
+    }
+    write!(f, ": {}", self.message)
+  }
+}
+impl std::error::Error for RegistryError {}
+impl RegistryError {
+  /// Byte offset of the problem into the source text.
+  ///
+  /// This is always known for errors from parsing a `&str`.
+  pub fn offset(&self) -> Option<usize> {
+    match self.position {
+      ErrorPosition::Located { offset, .. } => Some(offset),
+      _ => None,
+    }
+  }
+
+  /// Line of the source text (starting from 1) that the problem is on.
+  ///
+  /// This is always known for errors from parsing a `&str`.
+  pub fn line(&self) -> Option<usize> {
+    match self.position {
+      ErrorPosition::Located { line, .. } => Some(line),
+      _ => None,
+    }
+  }
+
+  /// Makes an error at the start of `loc`, which must be a slice of the
+  /// source text.
+  fn at(
+    loc: &str, element: &str, attribute: Option<&str>, message: String,
+  ) -> Self {
+    Self {
+      element: String::from(element),
+      attribute: attribute.map(String::from),
+      message,
+      position: ErrorPosition::Address(loc.as_ptr() as usize),
+    }
+  }
+
+  fn end_of_input(element: &str) -> Self {
+    Self {
+      element: String::from(element),
+      attribute: None,
+      message: String::from("unexpected end of input"),
+      position: ErrorPosition::EndOfInput,
+    }
+  }
+
+  fn unknown_attribute(element: &str, key: &str, value: &str) -> Self {
+    Self::at(
+      key,
+      element,
+      Some(key),
+      format!("unknown attribute (value {:?})", first_line_of(value)),
+    )
+  }
+
+  fn missing_attribute(loc: &str, element: &str, key: &str) -> Self {
+    Self::at(loc, element, Some(key), String::from("missing attribute"))
+  }
+
+  fn unexpected(element: &str, found: &XmlElement<'_>) -> Self {
+    let loc = match found {
+      StartTag { name, .. } | EndTag { name } | EmptyTag { name, .. } => name,
+      Text(t) | Comment(t) => t,
+    };
+    let found = format!("{:?}", found);
+    Self::at(
+      loc,
+      element,
+      None,
+      format!("unexpected content: {}", first_line_of(&found)),
+    )
+  }
+
+  /// Works out the offset and line of the problem within the `src` that was
+  /// parsed.
+  fn locate_within(mut self, src: &str) -> Self {
+    let base = src.as_ptr() as usize;
+    let offset = match self.position {
+      ErrorPosition::Address(addr)
+        if addr >= base && addr <= base + src.len() =>
+      {
+        addr - base
+      }
+      ErrorPosition::Located { offset, .. } => offset,
+      _ => src.len(),
+    };
+    let line =
+      1 + src.as_bytes()[..offset].iter().filter(|&&b| b == b'\n').count();
+    self.position = ErrorPosition::Located { offset, line };
+    self
+  }
+}
+
+/// Options for parsing `gl.xml` into a [`GlRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+  /// When `true`, any unknown attribute or tag is an error.
+  ///
+  /// When `false`, unknown attributes are ignored and unknown tags are skipped
+  /// (along with everything inside of them). Types that can't be understood
+  /// or that are only for some APIs, and enums, features, and extension
+  /// requirements for an unknown `api`, are skipped too. Each thing skipped is recorded as a [`ParseWarning`].
+  /// This lets you parse a newer registry than this version of `phosphorus`
+  /// knows about, as long as the new parts aren't things you need.
+  pub strict: bool,
+}
+impl Default for ParseOptions {
+  fn default() -> Self {
+    Self { strict: true }
+  }
+}
+
+/// Something in `gl.xml` that a lenient parse didn't understand and skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+  /// The path of tags leading to the skipped item, eg:
+  /// `registry/commands/command/param`.
+  ///
+  /// For an unknown tag this ends with the unknown tag's own name.
+  pub path: String,
+  /// The attribute key, `#text` for text that wasn't understood, or empty
+  /// for an unknown tag.
+  pub key: String,
+  /// The attribute value, the text, or the raw attribute text for an unknown
+  /// tag.
+  pub value: String,
+}
+impl ParseWarning {
+  /// The `key` of a warning about text that wasn't understood.
+  pub const TEXT_KEY: &'static str = "#text";
+}
+impl core::fmt::Display for ParseWarning {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.key.is_empty() {
+      write!(f, "skipped unknown tag <{}>", self.path)
+    } else if self.key == Self::TEXT_KEY {
+      write!(
+        f,
+        "skipped <{}> with unknown text {:?}",
+        self.path,
+        first_line_of(&self.value)
+      )
+    } else {
+      write!(
+        f,
+        "skipped unknown attribute <{}> {}={:?}",
+        self.path, self.key, self.value
+      )
+    }
+  }
+}
+
+/// The first line of `text`, cut down to about 60 chars, for showing a bit of
+/// some text in a one-line message.
+fn first_line_of(text: &str) -> String {
+  const MAX_CHARS: usize = 60;
+  let line = text.lines().next().unwrap_or("");
+  match line.char_indices().nth(MAX_CHARS) {
+    Some((end, _)) => format!("{}...", &line[..end]),
+    None if line.len() < text.trim_end().len() => format!("{}...", line),
+    None => String::from(line),
+  }
+}
+
+/// The options of a parse, and the warnings it has gathered so far.
+///
+/// Each `path` given to the methods here is the `/` separated path of tags
+/// from the root of the registry, eg: `registry/commands/command`.
+#[derive(Debug, Default)]
+struct ParseState {
+  options: ParseOptions,
+  warnings: Vec<ParseWarning>,
+}
+impl ParseState {
+  /// Handles an unknown attribute on the tag at `path`.
+  fn unknown_attribute(
+    &mut self, path: &str, key: &str, value: &str,
+  ) -> Result<(), RegistryError> {
+    if self.options.strict {
+      Err(RegistryError::unknown_attribute(last_path_part(path), key, value))
+    } else {
+      self.warnings.push(ParseWarning {
+        path: String::from(path),
+        key: String::from(key),
+        value: String::from(value),
+      });
+      Ok(())
+    }
+  }
+
+  /// Handles an `api` attribute on the tag at `path`, giving `None` if the
+  /// value isn't a known API (which is only allowed in a lenient parse).
+  fn api_attr(
+    &mut self, path: &str, value: &str,
+  ) -> Result<Option<ApiGroup>, RegistryError> {
+    match value.parse() {
+      Ok(api) => Ok(Some(api)),
+      Err(msg) if self.options.strict => {
+        Err(RegistryError::at(value, last_path_part(path), Some("api"), msg))
+      }
+      Err(_) => {
+        self.warnings.push(ParseWarning {
+          path: String::from(path),
+          key: String::from("api"),
+          value: String::from(value),
+        });
+        Ok(None)
+      }
+    }
+  }
+
+  /// Handles text within the tag at `path` that isn't understood, which is
+  /// only allowed in a lenient parse.
+  fn unknown_text(
+    &mut self, loc: &str, path: &str, message: &str, text: &str,
+  ) -> Result<(), RegistryError> {
+    if self.options.strict {
+      Err(RegistryError::at(
+        loc,
+        last_path_part(path),
+        None,
+        format!("{}: {}", message, first_line_of(text)),
+      ))
+    } else {
+      self.warnings.push(ParseWarning {
+        path: String::from(path),
+        key: String::from(ParseWarning::TEXT_KEY),
+        value: String::from(text),
+      });
+      Ok(())
+    }
+  }
+
+  /// Handles unexpected content within the tag at `path`.
+  ///
+  /// Unknown tags can be skipped, but anything else is always an error.
+  fn unexpected<'s>(
+    &mut self, iter: &mut impl Iterator<Item = XmlElement<'s>>, path: &str,
+    found: XmlElement<'s>,
+  ) -> Result<(), RegistryError> {
+    let (name, attrs) = match found {
+      StartTag { name, attrs } | EmptyTag { name, attrs }
+        if !self.options.strict =>
+      {
+        (name, attrs)
+      }
+      _ => return Err(RegistryError::unexpected(last_path_part(path), &found)),
+    };
+    if let StartTag { .. } = found {
+      skip_to_close(iter, name)?;
+    }
+    self.warnings.push(ParseWarning {
+      path: format!("{}/{}", path, name),
+      key: String::new(),
+      value: String::from(attrs),
+    });
+    Ok(())
+  }
+}
+
+fn last_path_part(path: &str) -> &str {
+  path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Skips past the close of a `name` tag we've just seen the start of,
+/// including any nested tags of the same name.
+fn skip_to_close<'s>(
+  iter: &mut impl Iterator<Item = XmlElement<'s>>, name: &str,
+) -> Result<(), RegistryError> {
+  let mut depth = 1_usize;
+  loop {
+    match next_in(iter, name)? {
+      StartTag { name: n, .. } if n == name => depth += 1,
+      EndTag { name: n } if n == name => {
+        depth -= 1;
+        if depth == 0 {
+          return Ok(());
+        }
+      }
+      _ => continue,
     }
-    //
-    Self { gl_types, gl_enums, gl_commands, api, version: level, extensions }
   }
 }
 
-fn revert_xml_encoding(text: String) -> String {
-  let mut out = String::with_capacity(text.as_bytes().len());
+/// Gets the next XML element, or an error if the input ran out while we were
+/// still inside of `element`.
+fn next_in<'s>(
+  iter: &mut impl Iterator<Item = XmlElement<'s>>, element: &str,
+) -> Result<XmlElement<'s>, RegistryError> {
+  iter.next().ok_or_else(|| RegistryError::end_of_input(element))
+}
+
+fn revert_xml_encoding(text: String) -> Option<String> {
+  let mut out = String::with_capacity(text.len());
   let mut chars = text.chars();
   while let Some(c) = chars.next() {
     if c != '&' {
       out.push(c);
     } else {
-      match chars.next().unwrap() {
-        'l' => {
-          assert_eq!(chars.next().unwrap(), 't');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('<');
-        }
-        'g' => {
-          assert_eq!(chars.next().unwrap(), 't');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('>');
-        }
-        'a' => {
-          assert_eq!(chars.next().unwrap(), 'm');
-          assert_eq!(chars.next().unwrap(), 'p');
-          assert_eq!(chars.next().unwrap(), ';');
-          out.push('&');
+      let (rest, replacement): (&[char], char) = match chars.next()? {
+        'l' => (&['t', ';'], '<'),
+        'g' => (&['t', ';'], '>'),
+        'a' => (&['m', 'p', ';'], '&'),
+        _ => return None,
+      };
+      for expected in rest.iter().copied() {
+        if chars.next()? != expected {
+          return None;
         }
-        other => panic!("{}", other),
       }
+      out.push(replacement);
     }
   }
-  out
+  Some(out)
 }
 
-fn eat_to_comment_close<'s>(iter: &mut impl Iterator<Item = XmlElement<'s>>) {
+fn eat_to_comment_close<'s>(
+  iter: &mut impl Iterator<Item = XmlElement<'s>>,
+) -> Result<(), RegistryError> {
   loop {
-    match iter.next().unwrap() {
-      EndTag { name: "comment" } => return,
+    match next_in(iter, "comment")? {
+      EndTag { name: "comment" } => return Ok(()),
       _ => continue,
     }
   }
 }
 
-fn eat_to_groups_close<'s>(iter: &mut impl Iterator<Item = XmlElement<'s>>) {
+fn gather_groups_to<'s>(
+  state: &mut ParseState, groups: &mut BTreeMap<String, GlGroup>,
+  iter: &mut impl Iterator<Item = XmlElement<'s>>,
+) -> Result<(), RegistryError> {
   loop {
-    match iter.next().unwrap() {
-      EndTag { name: "groups" } => return,
-      _ => continue,
+    let (attrs, is_empty_tag) = match next_in(iter, "groups")? {
+      EndTag { name: "groups" } => return Ok(()),
+      StartTag { name: "group", attrs } => (attrs, false),
+      EmptyTag { name: "group", attrs } => (attrs, true),
+      unknown => {
+        state.unexpected(iter, "registry/groups", unknown)?;
+        continue;
+      }
+    };
+    let mut group_name = None;
+    for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
+      match key {
+        "name" => group_name = Some(value),
+        "comment" => (),
+        unknown => {
+          state.unknown_attribute("registry/groups/group", unknown, value)?
+        }
+      }
+    }
+    let group_name = group_name.ok_or_else(|| {
+      RegistryError::missing_attribute(attrs, "group", "name")
+    })?;
+    let group = groups.entry(String::from(group_name)).or_insert_with(|| {
+      GlGroup { name: String::from(group_name), ..GlGroup::default() }
+    });
+    if is_empty_tag {
+      continue;
+    }
+    loop {
+      match next_in(iter, "group")? {
+        EndTag { name: "group" } => break,
+        EmptyTag { name: "enum", attrs } => {
+          for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
+            match key {
+              "name" => group.enums.push(String::from(value)),
+              "comment" => (),
+              unknown => state.unknown_attribute(
+                "registry/groups/group/enum",
+                unknown,
+                value,
+              )?,
+            }
+          }
+        }
+        unknown => state.unexpected(iter, "registry/groups/group", unknown)?,
+      }
     }
   }
 }
 
 fn grab_out_name_text<'s>(
   iter: &mut impl Iterator<Item = XmlElement<'s>>,
-) -> &'s str {
-  let t = match iter.next().unwrap() {
+) -> Result<&'s str, RegistryError> {
+  let t = match next_in(iter, "name")? {
     Text(t) => t,
-    unknown => panic!("grab_out_name_text err:{:?}", unknown),
+    unknown => return Err(RegistryError::unexpected("name", &unknown)),
   };
-  assert!(matches!(iter.next().unwrap(), EndTag { name: "name" }));
-  t
+  match next_in(iter, "name")? {
+    EndTag { name: "name" } => Ok(t),
+    unknown => Err(RegistryError::unexpected("name", &unknown)),
+  }
 }
 
 fn grab_out_ptype_text<'s>(
   iter: &mut impl Iterator<Item = XmlElement<'s>>,
-) -> &'s str {
-  let t = match iter.next().unwrap() {
+) -> Result<&'s str, RegistryError> {
+  let t = match next_in(iter, "ptype")? {
     Text(t) => t,
-    unknown => panic!("grab_out_ptype_text err:{:?}", unknown),
+    unknown => return Err(RegistryError::unexpected("ptype", &unknown)),
   };
-  assert!(matches!(iter.next().unwrap(), EndTag { name: "ptype" }));
-  t
+  match next_in(iter, "ptype")? {
+    EndTag { name: "ptype" } => Ok(t),
+    unknown => Err(RegistryError::unexpected("ptype", &unknown)),
+  }
 }
 
 /// Holds all the info accumulated from `gl.xml`.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlRegistry {
   /// The special types we need to support.
   pub gl_types: Vec<GlType>,
@@ -733,73 +3642,627 @@ pub struct GlRegistry {
   pub gl_features: Vec<GlFeature>,
   /// The vendor extensions that you might wish to also try using.
   pub gl_extensions: Vec<GlExtension>,
+  /// The groups of enums, by group name.
+  ///
+  /// This combines the `groups` section of `gl.xml` (if any) with the `group`
+  /// attribute of each enum.
+  pub gl_groups: BTreeMap<String, GlGroup>,
+}
+
+/// Looks up the items of a [`GlRegistry`] by name, without searching the
+/// lists each time.
+///
+/// Get one from [`GlRegistry::index`] when you have a lot of names to look
+/// up. It borrows the registry, so it can't get out of date.
+///
+/// When a name appears more than once the first item is kept, since that's
+/// the one a search from the front of the list would find.
+#[derive(Debug, Clone)]
+pub struct RegistryIndex<'r> {
+  gl_enums: HashMap<&'r str, &'r GlEnum>,
+  gl_commands: HashMap<&'r str, &'r GlCommand>,
+  gl_extensions: HashMap<&'r str, &'r GlExtension>,
+}
+impl<'r> RegistryIndex<'r> {
+  fn by_name<T>(
+    list: &'r [T], name_of: impl Fn(&'r T) -> &'r str,
+  ) -> HashMap<&'r str, &'r T> {
+    let mut out = HashMap::new();
+    for item in list.iter() {
+      out.entry(name_of(item)).or_insert(item);
+    }
+    out
+  }
+
+  /// Gets a command by name.
+  pub fn command(&self, name: &str) -> Option<&'r GlCommand> {
+    self.gl_commands.get(name).copied()
+  }
+
+  /// Gets an enum by name.
+  pub fn enum_(&self, name: &str) -> Option<&'r GlEnum> {
+    self.gl_enums.get(name).copied()
+  }
+
+  /// Gets an extension by name.
+  pub fn extension(&self, name: &str) -> Option<&'r GlExtension> {
+    self.gl_extensions.get(name).copied()
+  }
+
+  /// Groups the command names by the command at the end of their `alias_of`
+  /// links, with each group in the order that the loaders try them.
+  fn command_alias_sets(&self) -> BTreeMap<&'r str, Vec<&'r str>> {
+    let mut sets: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for name in self.gl_commands.keys().copied() {
+      sets.entry(self.alias_root(name)).or_default().push(name);
+    }
+    for (root, names) in sets.iter_mut() {
+      names.sort_by_key(|name| (name != root, alias_vendor_rank(name), *name));
+    }
+    sets
+  }
+
+  /// Follows a command's `alias_of` links to the end.
+  fn alias_root<'a>(&self, name: &'a str) -> &'a str
+  where
+    'r: 'a,
+  {
+    let mut root = name;
+    // the limit keeps a registry with an alias loop from hanging us.
+    for _ in 0..self.gl_commands.len() {
+      match self.command(root).and_then(|glc| glc.alias_of.as_deref()) {
+        Some(alias_of) => root = alias_of,
+        None => break,
+      }
+    }
+    root
+  }
 }
+
+/// Where a command's vendor suffix puts it among the other names in its alias
+/// set, with the most widely supported vendors first.
+fn alias_vendor_rank(name: &str) -> usize {
+  ["ARB", "KHR", "OES", "EXT"]
+    .iter()
+    .position(|vendor| name.ends_with(vendor))
+    .unwrap_or(4)
+}
+
 impl GlRegistry {
+  /// Gets the group of enums allowed for a particular parameter of a
+  /// particular command, if the parameter has a known group.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(
+  ///   r#"<registry>
+  ///   <enums namespace="GL">
+  ///     <enum value="0x2800" name="GL_TEXTURE_MAG_FILTER" group="TextureParameterName"/>
+  ///     <enum value="0x2801" name="GL_TEXTURE_MIN_FILTER" group="TextureParameterName"/>
+  ///   </enums>
+  ///   <commands namespace="GL">
+  ///     <command>
+  ///       <proto>void <name>glTexParameteri</name></proto>
+  ///       <param group="TextureTarget"><ptype>GLenum</ptype> <name>target</name></param>
+  ///       <param group="TextureParameterName"><ptype>GLenum</ptype> <name>pname</name></param>
+  ///       <param><ptype>GLint</ptype> <name>param</name></param>
+  ///     </command>
+  ///   </commands>
+  ///   </registry>"#,
+  /// );
+  /// let group = registry.group_for_param("glTexParameteri", "pname").unwrap();
+  /// assert_eq!(group.enums, ["GL_TEXTURE_MAG_FILTER", "GL_TEXTURE_MIN_FILTER"]);
+  /// ```
+  pub fn group_for_param(
+    &self, command: &str, param: &str,
+  ) -> Option<&GlGroup> {
+    let gl_command = self.command(command)?;
+    let gl_param = gl_command.params.iter().find(|p| p.name() == param)?;
+    self.gl_groups.get(gl_param.group()?)
+  }
+
+  /// Gets a command by name.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(
+  ///   r#"<registry>
+  ///   <commands namespace="GL">
+  ///     <command><proto>void <name>glFlush</name></proto></command>
+  ///     <command><proto>void <name>glFinish</name></proto></command>
+  ///   </commands>
+  ///   </registry>"#,
+  /// );
+  /// assert_eq!(registry.command("glFinish").unwrap().name, "glFinish");
+  /// assert!(registry.command("glSwapBuffers").is_none());
+  /// ```
+  pub fn command(&self, name: &str) -> Option<&GlCommand> {
+    self.gl_commands.iter().find(|glc| glc.name == name)
+  }
+
+  /// The other names that a command can be loaded by, in the order that the
+  /// generated loaders try them when the command's own name doesn't load.
+  ///
+  /// These are the commands that end up at the same command when you follow
+  /// their `alias_of` links. That command comes first, then the `ARB`, `KHR`,
+  /// `OES`, and `EXT` names, then any other vendor's names.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\">",
+  ///   "<command><proto>void <name>glBindBuffer</name></proto></command>",
+  ///   "<command><proto>void <name>glBindBufferNV</name></proto>",
+  ///   "<alias name=\"glBindBufferEXT\"/></command>",
+  ///   "<command><proto>void <name>glBindBufferEXT</name></proto>",
+  ///   "<alias name=\"glBindBuffer\"/></command>",
+  ///   "<command><proto>void <name>glBindBufferARB</name></proto>",
+  ///   "<alias name=\"glBindBuffer\"/></command>",
+  ///   "<command><proto>void <name>glFlush</name></proto></command>",
+  ///   "</commands></registry>",
+  /// ));
+  /// assert_eq!(
+  ///   registry.command_aliases("glBindBufferNV"),
+  ///   ["glBindBuffer", "glBindBufferARB", "glBindBufferEXT"]
+  /// );
+  /// assert_eq!(
+  ///   registry.command_aliases("glBindBuffer"),
+  ///   ["glBindBufferARB", "glBindBufferEXT", "glBindBufferNV"]
+  /// );
+  /// assert!(registry.command_aliases("glFlush").is_empty());
+  /// ```
+  pub fn command_aliases(&self, name: &str) -> Vec<&str> {
+    let index = self.index();
+    let mut sets = index.command_alias_sets();
+    let mut aliases = sets.remove(index.alias_root(name)).unwrap_or_default();
+    aliases.retain(|alias| *alias != name);
+    aliases
+  }
+
+  /// Gets an enum by name.
+  ///
+  /// Some enums are listed more than once (with a different `api`), in which
+  /// case this gives the first one.
+  pub fn enum_(&self, name: &str) -> Option<&GlEnum> {
+    self.gl_enums.iter().find(|gle| gle.name == name)
+  }
+
+  /// Gets an extension by name.
+  pub fn extension(&self, name: &str) -> Option<&GlExtension> {
+    self.gl_extensions.iter().find(|ext| ext.name == name)
+  }
+
+  /// Indexes the commands, enums, and extensions by name, for when you have
+  /// a lot of names to look up.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(
+  ///   r#"<registry>
+  ///   <commands namespace="GL">
+  ///     <command><proto>void <name>glFlush</name></proto></command>
+  ///   </commands>
+  ///   </registry>"#,
+  /// );
+  /// let index = registry.index();
+  /// assert_eq!(index.command("glFlush").unwrap().name, "glFlush");
+  /// assert!(index.enum_("glFlush").is_none());
+  /// ```
+  pub fn index(&self) -> RegistryIndex<'_> {
+    RegistryIndex {
+      gl_enums: RegistryIndex::by_name(&self.gl_enums, |gle| &gle.name),
+      gl_commands: RegistryIndex::by_name(&self.gl_commands, |glc| &glc.name),
+      gl_extensions: RegistryIndex::by_name(&self.gl_extensions, |ext| {
+        &ext.name
+      }),
+    }
+  }
+
+  /// Gives the whole registry as JSON, for tools that aren't written in Rust.
+  ///
+  /// The JSON follows the Rust types, so the docs of each type also describe
+  /// the JSON:
+  /// * A struct is an object with a key for each of its fields, using the same
+  ///   names. A [`GlCommandParam`] has `text` (its C declaration, such as
+  ///   `const void * data`), `name` (such as `data`), `group`, and `len`, and
+  ///   a [`GlRemoval`] has `profile` and `adjustment`.
+  /// * A missing `Option` is `null`. A [`GlVersion`] is `{"major": 4,
+  ///   "minor": 6}`.
+  /// * [`ApiGroup`] and [`GlProfile`] are lowercase strings, such as `"gles2"`
+  ///   and `"core"`.
+  /// * [`ReqRem`] and [`GlType`] are objects with one key, the lowercase name
+  ///   of the variant, such as `{"command": "glFlush"}` or `{"typedef":
+  ///   "typedef int GLint;"}`.
+  ///
+  /// Only new keys are added in later versions of this crate, so readers
+  /// should ignore keys they don't know.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\">",
+  ///   "<command><proto>void <name>glFlush</name></proto></command>",
+  ///   "</commands>",
+  ///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\">",
+  ///   "<require><command name=\"glFlush\"/></require></feature>",
+  ///   "</registry>",
+  /// ));
+  /// let json = registry.to_json();
+  /// assert!(json.contains(r#""adjustment": {"#));
+  /// assert!(json.contains(r#""command": "glFlush""#));
+  /// let loaded = GlRegistry::from_json(&json).unwrap();
+  /// assert_eq!(loaded.command("glFlush").unwrap().proto, "void glFlush");
+  /// assert_eq!(loaded.gl_features[0].api, ApiGroup::Gl);
+  /// ```
+  #[cfg(feature = "json")]
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+
+  /// Rebuilds a registry from the JSON that [`to_json`](Self::to_json) gives.
+  #[cfg(feature = "json")]
+  pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+    serde_json::from_str(json)
+  }
+
+  /// Explains where a command or enum comes from: the features that require
+  /// or remove it, the extensions that provide it, and how it's related to
+  /// other commands or enums.
+  ///
+  /// Gives `None` if there's no command or enum with that name.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(
+  ///   r#"<registry>
+  ///   <commands namespace="GL">
+  ///     <command><proto>void <name>glBufferStorage</name></proto></command>
+  ///     <command>
+  ///       <proto>void <name>glBufferStorageEXT</name></proto>
+  ///       <alias name="glBufferStorage"/>
+  ///     </command>
+  ///   </commands>
+  ///   <feature api="gl" name="GL_VERSION_4_4" number="4.4">
+  ///     <require><command name="glBufferStorage"/></require>
+  ///   </feature>
+  ///   <extensions>
+  ///     <extension name="GL_EXT_buffer_storage" supported="gles2">
+  ///       <require><command name="glBufferStorageEXT"/></require>
+  ///     </extension>
+  ///   </extensions>
+  ///   </registry>"#,
+  /// );
+  /// let query = registry.query("glBufferStorage").unwrap();
+  /// assert_eq!(query.required_by[0].feature, "GL_VERSION_4_4");
+  /// assert!(query.extensions.is_empty());
+  /// assert_eq!(query.aliases, ["glBufferStorageEXT"]);
+  /// let query = registry.query("glBufferStorageEXT").unwrap();
+  /// assert_eq!(query.extensions[0].extension, "GL_EXT_buffer_storage");
+  /// assert_eq!(query.alias_of.as_deref(), Some("glBufferStorage"));
+  /// ```
+  pub fn query(&self, name: &str) -> Option<ItemQuery> {
+    let mut query = match (self.command(name), self.enum_(name)) {
+      (Some(gl_command), _) => ItemQuery {
+        kind: ItemKind::Command,
+        alias_of: gl_command.alias_of.clone(),
+        aliases: self
+          .gl_commands
+          .iter()
+          .filter(|glc| glc.alias_of.as_deref() == Some(name))
+          .map(|glc| glc.name.clone())
+          .collect(),
+        vec_equivalent: gl_command.vec_equivalent.clone(),
+        ..ItemQuery::default()
+      },
+      (None, Some(gl_enum)) => ItemQuery {
+        kind: ItemKind::Enum,
+        alias_of: gl_enum.alias_of.clone(),
+        aliases: self
+          .gl_enums
+          .iter()
+          .filter(|gle| gle.alias_of.as_deref() == Some(name))
+          .map(|gle| gle.name.clone())
+          .collect(),
+        ..ItemQuery::default()
+      },
+      (None, None) => return None,
+    };
+    query.name = String::from(name);
+    query.aliases.sort();
+    query.aliases.dedup();
+    let is_this = |adjustment: &ReqRem| match (adjustment, query.kind) {
+      (ReqRem::Command(n), ItemKind::Command) => n == name,
+      (ReqRem::Enum(n), ItemKind::Enum) => n == name,
+      _ => false,
+    };
+    let mut required_by = Vec::new();
+    let mut removed_by = Vec::new();
+    for gl_feature in self.gl_features.iter() {
+      let feature_use = |profile: &Option<String>| FeatureUse {
+        feature: gl_feature.name.clone(),
+        api: gl_feature.api,
+        number: gl_feature.number,
+        profile: profile.clone(),
+      };
+      for req in gl_feature.required.iter() {
+        if is_this(&req.adjustment) {
+          required_by.push(feature_use(&req.profile));
+        }
+      }
+      for rem in gl_feature.remove.iter() {
+        if is_this(&rem.adjustment) {
+          removed_by.push(feature_use(&rem.profile));
+        }
+      }
+    }
+    let mut extensions = Vec::new();
+    for gl_extension in self.gl_extensions.iter() {
+      for req in gl_extension.required.iter() {
+        if is_this(&req.adjustment) {
+          extensions.push(ExtensionUse {
+            extension: gl_extension.name.clone(),
+            supported: match req.api {
+              Some(api) => String::from(api.supported()),
+              None => gl_extension.supported.clone(),
+            },
+            profile: req.profile.clone(),
+          });
+        }
+      }
+    }
+    extensions.dedup();
+    query.required_by = required_by;
+    query.removed_by = removed_by;
+    query.extensions = extensions;
+    Some(query)
+  }
+
+  /// Adds each enum to the groups listed in its `group` attribute, then puts
+  /// every group's enum list in order.
+  fn gather_enum_groups(&mut self) {
+    for gl_enum in self.gl_enums.iter() {
+      if let Some(group_text) = gl_enum.group.as_ref() {
+        for group_name in group_text.split(',') {
+          self
+            .gl_groups
+            .entry(String::from(group_name))
+            .or_insert_with(|| GlGroup {
+              name: String::from(group_name),
+              ..GlGroup::default()
+            })
+            .enums
+            .push(gl_enum.name.clone());
+        }
+      }
+    }
+    for group in self.gl_groups.values_mut() {
+      group.enums.sort();
+      group.enums.dedup();
+    }
+  }
+
   /// This is how you parse the contents of `gl.xml` into a `GlRegistry`.
-  pub fn from_gl_xml_str(mut gl_xml: &str) -> Self {
-    if gl_xml.chars().nth(0).unwrap() == '\u{feff}' {
-      gl_xml = &gl_xml['\u{feff}'.len_utf8()..];
+  ///
+  /// ## Panics
+  /// If the registry can't be parsed. Use
+  /// [`try_from_gl_xml_str`](Self::try_from_gl_xml_str) to get an error value
+  /// instead.
+  pub fn from_gl_xml_str(gl_xml: &str) -> Self {
+    match Self::try_from_gl_xml_str(gl_xml) {
+      Ok(registry) => registry,
+      Err(e) => panic!("{}", e),
     }
-    let iter = &mut ElementIterator::new(&gl_xml)
+  }
+
+  /// Parses the contents of `gl.xml` into a `GlRegistry`.
+  ///
+  /// If any part of the input isn't understood you get an error naming the
+  /// tag, attribute, and position of the problem.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let err = GlRegistry::try_from_gl_xml_str(
+  ///   "<registry>\n<commands namespace=\"GL\">\n<command kind=\"new\">",
+  /// )
+  /// .unwrap_err();
+  /// assert_eq!(err.element, "command");
+  /// assert_eq!(err.attribute.as_deref(), Some("kind"));
+  /// assert_eq!(err.line(), Some(3));
+  /// assert_eq!(err.offset(), Some(46));
+  /// let err = GlRegistry::try_from_gl_xml_str("<registry>\n<types>").unwrap_err();
+  /// assert_eq!(err.message, "unexpected end of input");
+  /// assert_eq!((err.line(), err.offset()), (Some(2), Some(18)));
+  /// ```
+  /// Only the first line of any offending text is shown.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let err = GlRegistry::try_from_gl_xml_str(
+  ///   "<registry><types><type>#ifndef X\n#define X\n#endif</type>",
+  /// )
+  /// .unwrap_err();
+  /// assert_eq!(err.message, "unknown GlType variant: #ifndef X...");
+  /// assert_eq!(err.to_string().lines().count(), 1);
+  /// ```
+  pub fn try_from_gl_xml_str(gl_xml: &str) -> Result<Self, RegistryError> {
+    Self::try_from_gl_xml_str_with_options(gl_xml, ParseOptions::default())
+      .map(|(registry, _warnings)| registry)
+  }
+
+  /// Parses the contents of `gl.xml` into a `GlRegistry` using the options
+  /// given.
+  ///
+  /// Along with the registry you get any warnings about things that a lenient
+  /// parse skipped over. A strict parse never has warnings.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let (registry, warnings) = GlRegistry::try_from_gl_xml_str_with_options(
+  ///   "<registry><extensions><extension name=\"GL_EXT_x\" supported=\"gl\" \
+  ///   kind=\"new\"><docs/></extension></extensions></registry>",
+  ///   ParseOptions { strict: false },
+  /// )
+  /// .unwrap();
+  /// assert_eq!(registry.gl_extensions[0].name, "GL_EXT_x");
+  /// assert_eq!(warnings.len(), 2);
+  /// assert_eq!(warnings[0].path, "registry/extensions/extension");
+  /// assert_eq!(warnings[0].key, "kind");
+  /// assert_eq!(warnings[0].value, "new");
+  /// assert_eq!(warnings[1].path, "registry/extensions/extension/docs");
+  /// ```
+  /// Types that can't be understood and things for an unknown `api` are
+  /// skipped the same way.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let (registry, warnings) = GlRegistry::try_from_gl_xml_str_with_options(
+  ///   "<registry><types><type>#ifndef X\n#define X\n#endif</type></types>\
+  ///   <enums namespace=\"GL\" group=\"G\" type=\"bitmask\">\
+  ///   <enum value=\"0x1\" name=\"GL_A\" api=\"gl9\"/>\
+  ///   <enum value=\"0x2\" name=\"GL_B\"/></enums>\
+  ///   <feature api=\"gl9\" name=\"GL_VERSION_9_0\" number=\"9.0\"></feature>\
+  ///   </registry>",
+  ///   ParseOptions { strict: false },
+  /// )
+  /// .unwrap();
+  /// assert!(registry.gl_types.is_empty());
+  /// assert_eq!(registry.gl_enums.len(), 1);
+  /// assert_eq!(registry.gl_enums[0].name, "GL_B");
+  /// assert!(registry.gl_features.is_empty());
+  /// assert_eq!(warnings.len(), 3);
+  /// assert_eq!(warnings[0].key, ParseWarning::TEXT_KEY);
+  /// assert_eq!(
+  ///   warnings[0].to_string(),
+  ///   "skipped <registry/types/type> with unknown text \"#ifndef X...\""
+  /// );
+  /// assert_eq!(warnings[1].path, "registry/enums/enum");
+  /// assert_eq!(warnings[1].key, "api");
+  /// assert_eq!(warnings[1].value, "gl9");
+  /// assert_eq!(warnings[2].path, "registry/feature");
+  /// # assert!(GlRegistry::try_from_gl_xml_str(
+  /// #   "<registry><feature api=\"gl9\" name=\"GL_VERSION_9_0\" number=\"9.0\">\
+  /// #   </feature></registry>"
+  /// # )
+  /// # .is_err());
+  /// ```
+  /// Every type that's kept is one that has a Rust equivalent, and types that
+  /// are only for some APIs aren't kept at all.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let gl_xml = "<registry><types>\
+  ///   <type>typedef unsigned long <name>GLnew</name>;</type>\
+  ///   <type api=\"gles2\">typedef int <name>GLint</name>;</type>\
+  ///   <type>typedef unsigned int <name>GLuint</name>;</type>\
+  ///   </types></registry>";
+  /// let (registry, warnings) = GlRegistry::try_from_gl_xml_str_with_options(
+  ///   gl_xml,
+  ///   ParseOptions { strict: false },
+  /// )
+  /// .unwrap();
+  /// assert_eq!(registry.gl_types.len(), 1);
+  /// assert_eq!(registry.gl_types[0].to_string(), "pub type GLuint = c_uint;");
+  /// assert_eq!(warnings.len(), 2);
+  /// assert_eq!(warnings[0].value, "typedef unsigned long GLnew;");
+  /// assert_eq!(warnings[1].key, "api");
+  /// let err = GlRegistry::try_from_gl_xml_str(gl_xml).unwrap_err();
+  /// assert_eq!(
+  ///   err.message,
+  ///   "no Rust equivalent for type: typedef unsigned long GLnew;"
+  /// );
+  /// ```
+  pub fn try_from_gl_xml_str_with_options(
+    gl_xml: &str, options: ParseOptions,
+  ) -> Result<(Self, Vec<ParseWarning>), RegistryError> {
+    let src = gl_xml.strip_prefix('\u{feff}').unwrap_or(gl_xml);
+    let iter = &mut ElementIterator::new(src)
       .filter_map(skip_comments)
       .filter_map(skip_empty_text_elements);
-    assert!(matches!(
-      iter.next().unwrap(),
-      StartTag { name: "registry", attrs: "" }
-    ));
-    Self::from_iter(iter)
+    let mut state = ParseState { options, warnings: Vec::new() };
+    let result = match next_in(iter, "registry") {
+      Ok(StartTag { name: "registry", attrs: "" }) => {
+        Self::try_from_iter_with_state(&mut state, iter)
+      }
+      Ok(unknown) => Err(RegistryError::unexpected("registry", &unknown)),
+      Err(e) => Err(e),
+    };
+    match result {
+      Ok(registry) => Ok((registry, state.warnings)),
+      Err(e) => Err(e.locate_within(gl_xml)),
+    }
   }
 
   /// Build a `GlRegistry` from the XML iterator.
   ///
   /// Must have `skip_comments` and `skip_empty_text_elements` applied.
+  ///
+  /// ## Panics
+  /// If the registry can't be parsed.
   #[doc(hidden)]
   #[allow(clippy::should_implement_trait)]
   pub fn from_iter<'s>(
     iter: &mut impl Iterator<Item = XmlElement<'s>>,
   ) -> Self {
+    match Self::try_from_iter(iter) {
+      Ok(registry) => registry,
+      Err(e) => panic!("{}", e),
+    }
+  }
+
+  /// Build a `GlRegistry` from the XML iterator.
+  ///
+  /// Must have `skip_comments` and `skip_empty_text_elements` applied.
+  ///
+  /// Any error has no `offset` or `line`, since this doesn't know the start
+  /// of the source text.
+  #[doc(hidden)]
+  pub fn try_from_iter<'s>(
+    iter: &mut impl Iterator<Item = XmlElement<'s>>,
+  ) -> Result<Self, RegistryError> {
+    Self::try_from_iter_with_state(&mut ParseState::default(), iter)
+  }
+
+  fn try_from_iter_with_state<'s>(
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+  ) -> Result<Self, RegistryError> {
     let mut registry = Self::default();
     loop {
-      match iter.next().unwrap() {
-        EndTag { name: "registry" } => return registry,
-        StartTag { name: "comment", attrs: "" } => eat_to_comment_close(iter),
-        StartTag { name: "groups", attrs: "" } => eat_to_groups_close(iter),
+      match next_in(iter, "registry")? {
+        EndTag { name: "registry" } => {
+          registry.gather_enum_groups();
+          return Ok(registry);
+        }
+        StartTag { name: "comment", attrs: "" } => eat_to_comment_close(iter)?,
+        StartTag { name: "groups", attrs: "" } => {
+          gather_groups_to(state, &mut registry.gl_groups, iter)?
+        }
         StartTag { name: "types", attrs: "" } => loop {
-          match iter.next().unwrap() {
+          match next_in(iter, "types")? {
             EndTag { name: "types" } => break,
             StartTag { name: "type", attrs } => {
-              if let Some(t) = GlType::try_from_iter_and_attrs(iter, attrs) {
+              if let Some(t) =
+                GlType::try_from_iter_and_attrs(state, iter, attrs)?
+              {
                 registry.gl_types.push(t)
               }
             }
-            unknown => panic!("unexpected 'type' tag content:{:?}", unknown),
+            unknown => state.unexpected(iter, "registry/types", unknown)?,
           }
         },
         StartTag { name: "enums", attrs } => {
-          gather_enum_entries_to(&mut registry.gl_enums, iter, attrs)
+          gather_enum_entries_to(state, &mut registry.gl_enums, iter, attrs)?
         }
         EmptyTag { name: "enums", attrs: _ } => {
           // Note(Lokathor): An empty enums tag is just like a start/end pair
           // except we define no enum entries, so we naturally just skip it.
         }
         StartTag { name: "commands", attrs: r#"namespace="GL""# } => loop {
-          match iter.next().unwrap() {
+          match next_in(iter, "commands")? {
             EndTag { name: "commands" } => break,
             StartTag { name: "command", attrs } => registry
               .gl_commands
-              .push(GlCommand::from_iter_and_attrs(iter, attrs)),
-            unknown => panic!("unknown 'commands' content:{:?}", unknown),
+              .push(GlCommand::from_iter_and_attrs(state, iter, attrs)?),
+            unknown => state.unexpected(iter, "registry/commands", unknown)?,
           }
         },
-        StartTag { name: "feature", attrs } => {
-          registry.gl_features.push(GlFeature::from_iter_and_attrs(iter, attrs))
-        }
+        StartTag { name: "feature", attrs } => registry
+          .gl_features
+          .extend(GlFeature::from_iter_and_attrs(state, iter, attrs)?),
         StartTag { name: "extensions", attrs: "" } => loop {
-          match iter.next().unwrap() {
+          match next_in(iter, "extensions")? {
             EndTag { name: "extensions" } => break,
             StartTag { name: "extension", attrs } => registry
               .gl_extensions
-              .push(GlExtension::from_iter_and_attrs(iter, attrs)),
+              .push(GlExtension::from_iter_and_attrs(state, iter, attrs)?),
             EmptyTag { name: "extension", attrs } => {
               let mut extension = GlExtension::default();
               for TagAttribute { key, value } in
@@ -808,22 +4271,556 @@ impl GlRegistry {
                 match key {
                   "name" => extension.name.push_str(value),
                   "supported" => extension.supported.push_str(value),
-                  unknown => panic!("unknown: {:?}", unknown),
+                  unknown => state.unknown_attribute(
+                    "registry/extensions/extension",
+                    unknown,
+                    value,
+                  )?,
                 }
               }
               registry.gl_extensions.push(extension);
             }
-            unknown => panic!("{:?}", unknown),
+            unknown => {
+              state.unexpected(iter, "registry/extensions", unknown)?
+            }
           }
         },
-        unknown => panic!("GlRegistry::from_iter:{:?}", unknown),
+        unknown => state.unexpected(iter, "registry", unknown)?,
+      }
+    }
+  }
+}
+
+/// If an [`ItemQuery`] is about a command or an enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemKind {
+  /// A GL function.
+  #[default]
+  Command,
+  /// A GL constant.
+  Enum,
+}
+
+/// Where a command or enum comes from, as explained by
+/// [`GlRegistry::query`].
+///
+/// The `Display` impl gives a summary meant for people to read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemQuery {
+  /// The name of the command or enum.
+  pub name: String,
+  /// If it's a command or an enum.
+  pub kind: ItemKind,
+  /// The features that add it.
+  pub required_by: Vec<FeatureUse>,
+  /// The features that take it away.
+  pub removed_by: Vec<FeatureUse>,
+  /// The extensions that add it.
+  pub extensions: Vec<ExtensionUse>,
+  /// What this is an alias of, if anything.
+  pub alias_of: Option<String>,
+  /// The things that are an alias of this.
+  pub aliases: Vec<String>,
+  /// For a command, the command that takes its arguments by pointer instead.
+  pub vec_equivalent: Option<String>,
+}
+impl core::fmt::Display for ItemQuery {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let kind = match self.kind {
+      ItemKind::Command => "command",
+      ItemKind::Enum => "enum",
+    };
+    let profile_note = |profile: &Option<String>| match profile {
+      Some(profile) => format!(" ({} profile)", profile),
+      None => String::new(),
+    };
+    show!(f, "{} ({})", self.name, kind);
+    for (title, list) in
+      [("Required by", &self.required_by), ("Removed by", &self.removed_by)]
+    {
+      if list.is_empty() {
+        show!(f, "{}: none", title);
+      } else {
+        show!(f, "{}:", title);
+        for feature_use in list.iter() {
+          show!(
+            f,
+            "  {} {} {}{}",
+            feature_use.api.supported(),
+            feature_use.number,
+            feature_use.feature,
+            profile_note(&feature_use.profile)
+          );
+        }
+      }
+    }
+    if self.extensions.is_empty() {
+      show!(f, "Extensions: none");
+    } else {
+      show!(f, "Extensions:");
+      for extension_use in self.extensions.iter() {
+        show!(
+          f,
+          "  {} ({}){}",
+          extension_use.extension,
+          extension_use.supported,
+          profile_note(&extension_use.profile)
+        );
+      }
+    }
+    show!(f, "Alias of: {}", self.alias_of.as_deref().unwrap_or("none"));
+    if self.aliases.is_empty() {
+      show!(f, "Aliases: none");
+    } else {
+      show!(f, "Aliases: {}", self.aliases.join(", "));
+    }
+    if self.kind == ItemKind::Command {
+      show!(
+        f,
+        "Vector equivalent: {}",
+        self.vec_equivalent.as_deref().unwrap_or("none")
+      );
+    }
+    Ok(())
+  }
+}
+
+/// A feature that requires or removes an item, in an [`ItemQuery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureUse {
+  /// The feature's name, such as `GL_VERSION_4_4`.
+  pub feature: String,
+  /// The feature's API group.
+  pub api: ApiGroup,
+  /// The feature's version.
+  pub number: GlVersion,
+  /// The profile this applies to, if it's limited to one.
+  pub profile: Option<String>,
+}
+
+/// An extension that requires an item, in an [`ItemQuery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionUse {
+  /// The extension's name.
+  pub extension: String,
+  /// `|` separated list of API groups that get the item from this extension.
+  pub supported: String,
+  /// The profile this applies to, if it's limited to one.
+  pub profile: Option<String>,
+}
+
+/// What changed from one [`GlRegistry`] to another, such as when updating
+/// the `gl.xml` that bindings are generated from.
+///
+/// Every list is sorted by name. The `Display` impl gives a report meant for
+/// people to read.
+/// ```rust
+/// # use phosphorus::*;
+/// let old = GlRegistry::from_gl_xml_str(concat!(
+///   "<registry><enums namespace=\"GL\">",
+///   "<enum value=\"0x1\" name=\"GL_A\"/>",
+///   "<enum value=\"0x2\" name=\"GL_B\"/>",
+///   "</enums><commands namespace=\"GL\">",
+///   "<command><proto>void <name>glFoo</name></proto>",
+///   "<param><ptype>GLenum</ptype> <name>target</name></param></command>",
+///   "</commands></registry>",
+/// ));
+/// let new = GlRegistry::from_gl_xml_str(concat!(
+///   "<registry><enums namespace=\"GL\">",
+///   "<enum value=\"0x3\" name=\"GL_B\"/>",
+///   "<enum value=\"0x4\" name=\"GL_C\"/>",
+///   "</enums><commands namespace=\"GL\">",
+///   "<command><proto>void <name>glFoo</name></proto>",
+///   "<param group=\"TextureTarget\"><ptype>GLenum</ptype> <name>target</name></param></command>",
+///   "</commands></registry>",
+/// ));
+/// let diff = RegistryDiff::between(&old, &new);
+/// assert_eq!(diff.added_enums, ["GL_C"]);
+/// assert_eq!(diff.removed_enums, ["GL_A"]);
+/// assert_eq!(diff.changed_enums[0].to_string(), "GL_B: 0x2 -> 0x3");
+/// assert_eq!(
+///   diff.changed_commands[0].details,
+///   ["param `target` group: none -> `TextureTarget`"]
+/// );
+/// assert!(diff.to_string().contains("\nAdded enums (1):\n  GL_C\n"));
+/// assert!(RegistryDiff::between(&new, &new).is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryDiff {
+  /// Commands that only the newer registry has.
+  pub added_commands: Vec<String>,
+  /// Commands that only the older registry has.
+  pub removed_commands: Vec<String>,
+  /// Commands that both registries have, but with a different signature.
+  pub changed_commands: Vec<CommandChange>,
+  /// Enums that only the newer registry has.
+  pub added_enums: Vec<String>,
+  /// Enums that only the older registry has.
+  pub removed_enums: Vec<String>,
+  /// Enums that both registries have, but with a different value.
+  pub changed_enums: Vec<EnumChange>,
+  /// Extensions that only the newer registry has.
+  pub added_extensions: Vec<String>,
+  /// Extensions that only the older registry has.
+  pub removed_extensions: Vec<String>,
+  /// Features (API versions) that only the newer registry has.
+  pub added_features: Vec<String>,
+  /// Features (API versions) that only the older registry has.
+  pub removed_features: Vec<String>,
+}
+impl RegistryDiff {
+  /// Compares the `old` registry to the `new` one.
+  ///
+  /// Enums are matched up by name and by the API group they're limited to, so
+  /// an enum like `GL_ACTIVE_PROGRAM_EXT` that has a different value per API
+  /// group is compared one group at a time.
+  pub fn between(old: &GlRegistry, new: &GlRegistry) -> Self {
+    let (added_commands, removed_commands, changed_commands) =
+      diff_commands(old.gl_commands.iter(), new.gl_commands.iter());
+    let (added_enums, removed_enums, changed_enums) =
+      diff_enums(old.gl_enums.iter(), new.gl_enums.iter());
+    let (added_extensions, removed_extensions) = added_and_removed(
+      old.gl_extensions.iter().map(|ext| ext.name.as_str()),
+      new.gl_extensions.iter().map(|ext| ext.name.as_str()),
+    );
+    let (added_features, removed_features) = added_and_removed(
+      old.gl_features.iter().map(|feature| feature.name.as_str()),
+      new.gl_features.iter().map(|feature| feature.name.as_str()),
+    );
+    Self {
+      added_commands,
+      removed_commands,
+      changed_commands,
+      added_enums,
+      removed_enums,
+      changed_enums,
+      added_extensions,
+      removed_extensions,
+      added_features,
+      removed_features,
+    }
+  }
+
+  /// If the two registries had no differences.
+  pub fn is_empty(&self) -> bool {
+    self == &Self::default()
+  }
+}
+impl core::fmt::Display for RegistryDiff {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.is_empty() {
+      show!(f, "No differences.");
+    }
+    show_diff_section(f, "Added features", &self.added_features)?;
+    show_diff_section(f, "Removed features", &self.removed_features)?;
+    show_diff_section(f, "Added extensions", &self.added_extensions)?;
+    show_diff_section(f, "Removed extensions", &self.removed_extensions)?;
+    show_diff_section(f, "Added commands", &self.added_commands)?;
+    show_diff_section(f, "Removed commands", &self.removed_commands)?;
+    show_diff_section(f, "Changed commands", &self.changed_commands)?;
+    show_diff_section(f, "Added enums", &self.added_enums)?;
+    show_diff_section(f, "Removed enums", &self.removed_enums)?;
+    show_diff_section(f, "Changed enums", &self.changed_enums)?;
+    Ok(())
+  }
+}
+
+/// What changed from one [`GlApiSelection`] to another, such as when moving
+/// to a newer GL version.
+///
+/// Every list is sorted by name. The `Display` impl gives a report meant for
+/// people to read.
+/// ```rust
+/// # use phosphorus::*;
+/// let registry = GlRegistry::from_gl_xml_str(concat!(
+///   "<registry><commands namespace=\"GL\">",
+///   "<command><proto>void <name>glFlush</name></proto></command>",
+///   "<command><proto>void <name>glFinish</name></proto></command>",
+///   "</commands>",
+///   "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\">",
+///   "<require><command name=\"glFlush\"/></require></feature>",
+///   "<feature api=\"gl\" name=\"GL_VERSION_1_1\" number=\"1.1\">",
+///   "<require><command name=\"glFinish\"/></require></feature>",
+///   "</registry>",
+/// ));
+/// let select = |minor| {
+///   GlApiSelection::new_from_registry_api_extensions(
+///     &registry,
+///     ApiGroup::Gl,
+///     GlVersion::new(1, minor),
+///     GlProfile::Core,
+///     &[],
+///   )
+/// };
+/// let diff = SelectionDiff::between(&select(0), &select(1));
+/// assert_eq!(diff.added_commands, ["glFinish"]);
+/// assert!(diff.removed_commands.is_empty());
+/// assert_eq!(diff.to_string(), "Added commands (1):\n  glFinish\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionDiff {
+  /// Commands that only the newer selection has.
+  pub added_commands: Vec<String>,
+  /// Commands that only the older selection has.
+  pub removed_commands: Vec<String>,
+  /// Commands that both selections have, but with a different signature.
+  pub changed_commands: Vec<CommandChange>,
+  /// Enums that only the newer selection has.
+  pub added_enums: Vec<String>,
+  /// Enums that only the older selection has.
+  pub removed_enums: Vec<String>,
+  /// Enums that both selections have, but with a different value.
+  pub changed_enums: Vec<EnumChange>,
+  /// Extensions that only the newer selection has.
+  pub added_extensions: Vec<String>,
+  /// Extensions that only the older selection has.
+  pub removed_extensions: Vec<String>,
+}
+impl SelectionDiff {
+  /// Compares the `old` selection to the `new` one.
+  pub fn between(old: &GlApiSelection, new: &GlApiSelection) -> Self {
+    let (added_commands, removed_commands, changed_commands) =
+      diff_commands(old.gl_commands.values(), new.gl_commands.values());
+    let (added_enums, removed_enums, changed_enums) =
+      diff_enums(old.gl_enums.values(), new.gl_enums.values());
+    let (added_extensions, removed_extensions) = added_and_removed(
+      old.extensions.iter().map(String::as_str),
+      new.extensions.iter().map(String::as_str),
+    );
+    Self {
+      added_commands,
+      removed_commands,
+      changed_commands,
+      added_enums,
+      removed_enums,
+      changed_enums,
+      added_extensions,
+      removed_extensions,
+    }
+  }
+
+  /// If the two selections had no differences.
+  pub fn is_empty(&self) -> bool {
+    self == &Self::default()
+  }
+}
+impl core::fmt::Display for SelectionDiff {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    if self.is_empty() {
+      show!(f, "No differences.");
+    }
+    show_diff_section(f, "Added extensions", &self.added_extensions)?;
+    show_diff_section(f, "Removed extensions", &self.removed_extensions)?;
+    show_diff_section(f, "Added commands", &self.added_commands)?;
+    show_diff_section(f, "Removed commands", &self.removed_commands)?;
+    show_diff_section(f, "Changed commands", &self.changed_commands)?;
+    show_diff_section(f, "Added enums", &self.added_enums)?;
+    show_diff_section(f, "Removed enums", &self.removed_enums)?;
+    show_diff_section(f, "Changed enums", &self.changed_enums)?;
+    Ok(())
+  }
+}
+
+/// A command whose signature changed, in a [`RegistryDiff`] or
+/// [`SelectionDiff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandChange {
+  /// The name of the command.
+  pub name: String,
+  /// A line for each difference, such as ``param `pname` type: `GLenum` ->
+  /// `GLint` ``.
+  ///
+  /// The parameters are compared by position, and named with their newer
+  /// name.
+  pub details: Vec<String>,
+}
+impl CommandChange {
+  /// Compares two versions of a command, giving `None` if nothing that
+  /// matters to the bindings changed.
+  fn between(old: &GlCommand, new: &GlCommand) -> Option<Self> {
+    let mut details = Vec::new();
+    let mut note = |what: &str, old: Option<&str>, new: Option<&str>| {
+      if old != new {
+        details.push(format!(
+          "{}: {} -> {}",
+          what,
+          quoted_or_none(old),
+          quoted_or_none(new)
+        ));
+      }
+    };
+    let return_type = |gl_command: &GlCommand| {
+      let proto = gl_command.proto.trim_end();
+      normalize_c_type(&proto[..proto.len() - gl_command.name.len()])
+    };
+    note("return type", Some(&return_type(old)), Some(&return_type(new)));
+    note(
+      "return group",
+      old.proto_group.as_deref(),
+      new.proto_group.as_deref(),
+    );
+    for (old_param, new_param) in old.params.iter().zip(new.params.iter()) {
+      let param = format!("param `{}`", new_param.name());
+      note(
+        &format!("{} type", param),
+        Some(&normalize_c_type(old_param.c_type())),
+        Some(&normalize_c_type(new_param.c_type())),
+      );
+      note(&format!("{} len", param), old_param.len(), new_param.len());
+      note(&format!("{} group", param), old_param.group(), new_param.group());
+    }
+    if old.params.len() != new.params.len() {
+      details.push(format!(
+        "param count: {} -> {}",
+        old.params.len(),
+        new.params.len()
+      ));
+    }
+    if details.is_empty() {
+      None
+    } else {
+      Some(Self { name: new.name.clone(), details })
+    }
+  }
+}
+impl core::fmt::Display for CommandChange {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    write!(f, "{}", self.name)?;
+    for detail in self.details.iter() {
+      write!(f, "\n  {}", detail)?;
+    }
+    Ok(())
+  }
+}
+
+/// An enum whose value changed, in a [`RegistryDiff`] or [`SelectionDiff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnumChange {
+  /// The name of the enum.
+  pub name: String,
+  /// The API group the enum is limited to, if any.
+  pub api: Option<ApiGroup>,
+  /// The older value, as written in `gl.xml`.
+  pub old_value: String,
+  /// The newer value, as written in `gl.xml`.
+  pub new_value: String,
+}
+impl core::fmt::Display for EnumChange {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    match self.api {
+      Some(api) => write!(f, "{} ({})", self.name, api.supported())?,
+      None => write!(f, "{}", self.name)?,
+    }
+    write!(f, ": {} -> {}", self.old_value, self.new_value)
+  }
+}
+
+/// The names that only `new` has, and the names that only `old` has, each
+/// sorted and without repeats.
+fn added_and_removed<'a>(
+  old: impl Iterator<Item = &'a str>, new: impl Iterator<Item = &'a str>,
+) -> (Vec<String>, Vec<String>) {
+  let old: BTreeSet<&str> = old.collect();
+  let new: BTreeSet<&str> = new.collect();
+  (
+    new.difference(&old).map(|name| name.to_string()).collect(),
+    old.difference(&new).map(|name| name.to_string()).collect(),
+  )
+}
+
+/// The added, removed, and changed commands from the `old` commands to the
+/// `new` ones, matched up by name.
+fn diff_commands<'a>(
+  old: impl Iterator<Item = &'a GlCommand>,
+  new: impl Iterator<Item = &'a GlCommand>,
+) -> (Vec<String>, Vec<String>, Vec<CommandChange>) {
+  let by_name = |gl_command: &'a GlCommand| gl_command.name.as_str();
+  let (old, new) = (first_by_key(old, by_name), first_by_key(new, by_name));
+  let (added, removed) =
+    added_and_removed(old.keys().copied(), new.keys().copied());
+  let changed = old
+    .iter()
+    .filter_map(|(name, old_command)| {
+      CommandChange::between(old_command, new.get(name)?)
+    })
+    .collect();
+  (added, removed, changed)
+}
+
+/// The added, removed, and changed enums from the `old` enums to the `new`
+/// ones, matched up by name and API group.
+fn diff_enums<'a>(
+  old: impl Iterator<Item = &'a GlEnum>, new: impl Iterator<Item = &'a GlEnum>,
+) -> (Vec<String>, Vec<String>, Vec<EnumChange>) {
+  let by_key = |gl_enum: &'a GlEnum| {
+    (gl_enum.name.as_str(), gl_enum.api.map(|a| a.supported()))
+  };
+  let (old, new) = (first_by_key(old, by_key), first_by_key(new, by_key));
+  let (added, removed) = added_and_removed(
+    old.keys().map(|(name, _)| *name),
+    new.keys().map(|(name, _)| *name),
+  );
+  let changed = old
+    .iter()
+    .filter_map(|(key, old_enum)| {
+      let new_enum = new.get(key)?;
+      if old_enum.value == new_enum.value {
+        return None;
+      }
+      Some(EnumChange {
+        name: old_enum.name.clone(),
+        api: old_enum.api,
+        old_value: old_enum.value.clone(),
+        new_value: new_enum.value.clone(),
+      })
+    })
+    .collect();
+  (added, removed, changed)
+}
+
+/// Maps each key to the first item with that key.
+fn first_by_key<'a, K: Ord, T>(
+  items: impl Iterator<Item = &'a T>, key: impl Fn(&'a T) -> K,
+) -> BTreeMap<K, &'a T> {
+  let mut map = BTreeMap::new();
+  for item in items {
+    map.entry(key(item)).or_insert(item);
+  }
+  map
+}
+
+/// A C type with its spacing made uniform, so that only real changes show up
+/// in a [`CommandChange`].
+fn normalize_c_type(c_type: &str) -> String {
+  c_type.replace('*', " * ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn quoted_or_none(text: Option<&str>) -> String {
+  match text {
+    Some(text) => format!("`{}`", text),
+    None => String::from("none"),
+  }
+}
+
+/// Shows a titled list as part of a diff report, if it isn't empty.
+fn show_diff_section<T: core::fmt::Display>(
+  f: &mut core::fmt::Formatter, title: &str, items: &[T],
+) -> core::fmt::Result {
+  if !items.is_empty() {
+    show!(f, "{} ({}):", title, items.len());
+    for item in items.iter() {
+      for line in item.to_string().lines() {
+        show!(f, "  {}", line);
       }
     }
   }
+  Ok(())
 }
 
 /// Some sort of additional type we need to declare.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum GlType {
   /// A type alias for an existing type.
   Typedef(String),
@@ -832,20 +4829,35 @@ pub enum GlType {
   /// A type definition with conditional compilation in it.
   IfDef(String),
 }
+/// Panics if this isn't a type that phosphorus knows how to declare in Rust.
+/// Every type that comes out of parsing a registry is one that it knows.
 impl core::fmt::Display for GlType {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    match self.rust_decl() {
+      Ok(decl) => f.write_str(&decl),
+      Err(msg) => panic!("{}", msg),
+    }
+  }
+}
+impl GlType {
+  /// The Rust declaration of this type, or why there can't be one.
+  fn rust_decl(&self) -> Result<String, String> {
     match self {
       GlType::Typedef(s) => {
-        assert!(s.as_bytes().last().unwrap() == &b';');
-        let mut words_iter = s[..s.as_bytes().len() - 1].split_whitespace();
-        assert_eq!(words_iter.next().unwrap(), "typedef");
-        let mut new = words_iter.next_back().unwrap();
-        let old: &'static str = match words_iter.next().unwrap() {
-          "unsigned" => match words_iter.next().unwrap() {
+        let unknown = || format!("unknown typedef: {}", s);
+        let mut words_iter =
+          s.strip_suffix(';').ok_or_else(unknown)?.split_whitespace();
+        if words_iter.next() != Some("typedef") {
+          return Err(unknown());
+        }
+        let mut new = words_iter.next_back().ok_or_else(unknown)?;
+        let mut decl = String::new();
+        let old: &'static str = match words_iter.next().ok_or_else(unknown)? {
+          "unsigned" => match words_iter.next().ok_or_else(unknown)? {
             "int" => "c_uint",
             "char" => "c_uchar",
             "short" => "c_ushort",
-            unknown => panic!("unknown unsigned:{}", unknown),
+            _ => return Err(unknown()),
           },
           "void" => match words_iter.next() {
             None => "c_void",
@@ -871,17 +4883,16 @@ impl core::fmt::Display for GlType {
                 new = "GLVULKANPROCNV";
                 r#"Option<extern "system" fn()>"#
               }
-              unknown => panic!("unknown fn ptr:{:?}", unknown),
+              _ => return Err(unknown()),
             },
-            unknown => panic!("unknown void:{:?}", unknown),
+            _ => return Err(unknown()),
           },
-          "struct" => match words_iter.next().unwrap() {
-            "__GLsync" => {
-              write!(f, "#[doc(hidden)]pub struct __GLsync{{ _priv: u8 }} impl core::fmt::Debug for __GLsync {{ fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{ write!(f, \"__GLsync\") }} }}")?;
-              assert_eq!(words_iter.next().unwrap(), "*");
+          "struct" => match words_iter.next().ok_or_else(unknown)? {
+            "__GLsync" if words_iter.next() == Some("*") => {
+              decl.push_str("#[doc(hidden)]pub struct __GLsync{ _priv: u8 } impl core::fmt::Debug for __GLsync { fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result { write!(f, \"__GLsync\") } }");
               "*mut __GLsync"
             }
-            unknown => panic!("unknown struct:{}", unknown),
+            _ => return Err(unknown()),
           },
           "khronos_int8_t" => "i8",
           "khronos_uint8_t" => "u8",
@@ -898,91 +4909,142 @@ impl core::fmt::Display for GlType {
           "double" => "c_double",
           "int" => "c_int",
           "char" => "c_char",
-          unknown => panic!("unknown:{}", unknown),
+          _ => return Err(unknown()),
         };
-        write!(f, "pub type {new} = {old};", new = new, old = old)
+        decl.push_str(&format!(
+          "pub type {new} = {old};",
+          new = new,
+          old = old
+        ));
+        Ok(decl)
       }
       GlType::Struct(s) => {
-        let mut words_iter = s[..s.as_bytes().len() - 1].split_whitespace();
-        assert_eq!(words_iter.next().unwrap(), "struct");
-        let name = words_iter.next().unwrap();
-        write!(f, "pub struct {name}{{ _priv: u8 }} impl core::fmt::Debug for {name} {{ fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{ write!(f, \"{name}\") }} }}", name = name)
+        let mut words_iter =
+          s.strip_suffix(';').unwrap_or("").split_whitespace();
+        match (words_iter.next(), words_iter.next(), words_iter.next()) {
+          (Some("struct"), Some(name), None) => Ok(format!("pub struct {name}{{ _priv: u8 }} impl core::fmt::Debug for {name} {{ fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {{ write!(f, \"{name}\") }} }}", name = name)),
+          _ => Err(format!("unknown struct: {}", s)),
+        }
       }
       GlType::IfDef(s) => {
         const GL_HANDLE_ARB_RN: &str = "#ifdef __APPLE__\r\ntypedef void *GLhandleARB;\r\n#else\r\ntypedef unsigned int GLhandleARB;\r\n#endif";
         const GL_HANDLE_ARB_N: &str = "#ifdef __APPLE__\ntypedef void *GLhandleARB;\n#else\ntypedef unsigned int GLhandleARB;\n#endif";
         match s.as_str() {
-          GL_HANDLE_ARB_RN | GL_HANDLE_ARB_N => write!(
-            f,
-            r#"#[cfg(any(target_os="macos", target_os="ios"))]pub type GLhandleARB = *mut c_void;#[cfg(not(any(target_os="macos", target_os="ios")))]pub type GLhandleARB = c_uint;"#
-          ),
-          unknown => panic!("unknown ifdef: {}", unknown),
+          GL_HANDLE_ARB_RN | GL_HANDLE_ARB_N => Ok(String::from(
+            r#"#[cfg(any(target_os="macos", target_os="ios"))]pub type GLhandleARB = *mut c_void;#[cfg(not(any(target_os="macos", target_os="ios")))]pub type GLhandleARB = c_uint;"#,
+          )),
+          unknown => Err(format!("unknown ifdef: {}", unknown)),
         }
       }
     }
   }
-}
-impl GlType {
+
   fn try_from_iter_and_attrs<'s>(
-    iter: &mut impl Iterator<Item = XmlElement<'s>>, _attrs: &str,
-  ) -> Option<Self> {
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+    attrs: &str,
+  ) -> Result<Option<Self>, RegistryError> {
+    // Types are shared by all APIs, so an API specific one can't be kept.
+    let mut api_specific = false;
+    for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
+      match key {
+        "name" | "comment" | "requires" => (),
+        "api" => {
+          state.unknown_attribute("registry/types/type", key, value)?;
+          api_specific = true;
+        }
+        _ => state.unknown_attribute("registry/types/type", key, value)?,
+      }
+    }
     let mut out = String::new();
     loop {
-      match iter.next().unwrap() {
+      match next_in(iter, "type")? {
         EndTag { name: "type" } => break,
         StartTag { name: "name", attrs: "" } => {
           if !out.is_empty() {
             out.push(' ');
           }
-          out.push_str(grab_out_name_text(iter))
+          out.push_str(grab_out_name_text(iter)?)
         }
         Text(t) => out.push_str(t.trim()),
         EmptyTag { name: "apientry", attrs: "" } => (),
-        unknown => panic!("unknown: {:?}", unknown),
+        unknown => state.unexpected(iter, "registry/types/type", unknown)?,
       }
     }
-    out = revert_xml_encoding(out);
-    if out.starts_with("#include") {
-      None
+    out = revert_xml_encoding(out).ok_or_else(|| {
+      RegistryError::at(attrs, "type", None, String::from("bad XML escape"))
+    })?;
+    let gl_type = if api_specific || out.starts_with("#include") {
+      return Ok(None);
     } else if out.starts_with("typedef") {
-      Some(GlType::Typedef(out))
+      GlType::Typedef(out)
     } else if out.starts_with("struct") {
-      Some(GlType::Struct(out))
+      GlType::Struct(out)
     } else if out.starts_with("#ifdef") {
-      Some(GlType::IfDef(out))
+      GlType::IfDef(out)
     } else {
-      panic!("unknown GlType variant: {}", out);
+      state.unknown_text(
+        attrs,
+        "registry/types/type",
+        "unknown GlType variant",
+        &out,
+      )?;
+      return Ok(None);
+    };
+    match gl_type.rust_decl() {
+      Ok(_) => Ok(Some(gl_type)),
+      Err(_) => {
+        let (GlType::Typedef(text)
+        | GlType::Struct(text)
+        | GlType::IfDef(text)) = &gl_type;
+        state.unknown_text(
+          attrs,
+          "registry/types/type",
+          "no Rust equivalent for type",
+          text,
+        )?;
+        Ok(None)
+      }
     }
   }
 }
 
 fn gather_enum_entries_to<'s>(
-  list: &mut Vec<GlEnum>, iter: &mut impl Iterator<Item = XmlElement<'s>>,
-  attrs: &str,
-) {
+  state: &mut ParseState, list: &mut Vec<GlEnum>,
+  iter: &mut impl Iterator<Item = XmlElement<'s>>, attrs: &str,
+) -> Result<(), RegistryError> {
   let mut is_bitmask = false;
   for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
     match key {
-      "namespace" => assert_eq!(value, "GL"),
+      "namespace" if value != "GL" => {
+        return Err(RegistryError::at(
+          value,
+          "enums",
+          Some(key),
+          format!("expected namespace \"GL\", got {:?}", value),
+        ))
+      }
+      "namespace" => (),
       "group" | "comment" | "vendor" | "start" | "end" => (),
       "type" if value == "bitmask" => is_bitmask = true,
-      unknown => panic!("unknown enum attr: {:?}", unknown),
+      unknown => state.unknown_attribute("registry/enums", unknown, value)?,
     }
   }
   loop {
-    match iter.next().unwrap() {
+    match next_in(iter, "enums")? {
       EndTag { name: "enums" } => break,
       EmptyTag { name: "unused", attrs: _ } => (),
       EmptyTag { name: "enum", attrs } => {
-        list.push(GlEnum::from_attrs(attrs, is_bitmask));
+        list.extend(GlEnum::from_attrs(state, attrs, is_bitmask)?);
       }
-      unknown => panic!("unknown: {:?}", unknown),
+      unknown => state.unexpected(iter, "registry/enums", unknown)?,
     }
   }
+  Ok(())
 }
 
 /// A constant we need to declare.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlEnum {
   /// The name
   pub name: String,
@@ -1005,9 +5067,21 @@ pub struct GlEnum {
   pub is_bitmask: bool,
   /// The list of extensions that caused this to be included
   pub extensions: Option<Vec<String>>,
+  /// The API features this is limited to, in a selection that combines more
+  /// than one API. `None` means every API has it.
+  pub apis: Option<Vec<String>>,
+  /// The API features of `apis` that have this without needing any of the
+  /// `extensions`, in a selection that combines more than one API. `None`
+  /// means that `extensions` (if any) are needed by every API.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub core_apis: Option<Vec<String>>,
 }
 impl GlEnum {
-  fn from_attrs(attrs: &str, is_bitmask: bool) -> Self {
+  /// Gives `None` for an enum of an unknown API in a lenient parse.
+  fn from_attrs(
+    state: &mut ParseState, attrs: &str, is_bitmask: bool,
+  ) -> Result<Option<Self>, RegistryError> {
+    let mut known_api = true;
     let mut name = String::new();
     let mut the_value = String::new();
     let mut group = None;
@@ -1019,16 +5093,38 @@ impl GlEnum {
         "value" => the_value.push_str(value),
         "group" => group = Some(String::from(value)),
         "alias" => alias_of = Some(String::from(value)),
-        "api" => api = Some(ApiGroup::from(value)),
+        "api" => match state.api_attr("registry/enums/enum", value)? {
+          Some(a) => api = Some(a),
+          None => known_api = false,
+        },
         "comment" => (),
         "type" => (),
-        unknown => panic!("unknown enum attr: {:?}", unknown),
+        unknown => {
+          state.unknown_attribute("registry/enums/enum", unknown, value)?
+        }
       }
     }
     let value = the_value;
-    assert!(!name.is_empty());
-    assert!(!value.is_empty());
-    GlEnum { name, value, group, alias_of, api, is_bitmask, extensions: None }
+    if name.is_empty() {
+      return Err(RegistryError::missing_attribute(attrs, "enum", "name"));
+    }
+    if value.is_empty() {
+      return Err(RegistryError::missing_attribute(attrs, "enum", "value"));
+    }
+    if !known_api {
+      return Ok(None);
+    }
+    Ok(Some(GlEnum {
+      name,
+      value,
+      group,
+      alias_of,
+      api,
+      is_bitmask,
+      extensions: None,
+      apis: None,
+      core_apis: None,
+    }))
   }
 }
 
@@ -1091,26 +5187,11 @@ impl<'e> core::fmt::Display for GlEnumDisplayer<'e> {
       doc.push_str(a);
       doc.push_str("`\"]");
     }
-    let mut extensions = String::from("");
-    if let Some(list) = self.gl_enum.extensions.as_ref() {
-      extensions.push_str("#[cfg(any(");
-      for (i, l) in list.iter().enumerate() {
-        if i != 0 {
-          extensions.push(',');
-        }
-        show!(extensions, "feature=\"{l}\"", l = l);
-      }
-      extensions.push_str("))]");
-      //
-      extensions.push_str("#[cfg_attr(docs_rs, doc(cfg(any(");
-      for (i, l) in list.iter().enumerate() {
-        if i != 0 {
-          extensions.push(',');
-        }
-        show!(extensions, "feature=\"{l}\"", l = l);
-      }
-      extensions.push_str("))))]");
-    };
+    let extensions = item_cfg(
+      self.gl_enum.apis.as_ref(),
+      self.gl_enum.extensions.as_ref(),
+      self.gl_enum.core_apis.as_ref(),
+    );
     //
     write!(
       f,
@@ -1126,6 +5207,7 @@ impl<'e> core::fmt::Display for GlEnumDisplayer<'e> {
 
 /// A GL function we have to bind to.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct GlCommand {
   pub name: String,
@@ -1138,20 +5220,38 @@ pub struct GlCommand {
   /// "call this instead if you want to pass via pointer"
   pub vec_equivalent: Option<String>,
   pub extensions: Option<Vec<String>>,
+  /// The API features this is limited to, in a selection that combines more
+  /// than one API. `None` means every API has it.
+  pub apis: Option<Vec<String>>,
+  /// The API features of `apis` that have this without needing any of the
+  /// `extensions`, in a selection that combines more than one API. `None`
+  /// means that `extensions` (if any) are needed by every API.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub core_apis: Option<Vec<String>>,
+  /// In a selection, the other names that the loaders try, in order, when
+  /// this command's own name doesn't load. See
+  /// [`GlRegistry::command_aliases`]. Always empty in a registry.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub load_aliases: Vec<String>,
 }
 impl GlCommand {
   fn from_iter_and_attrs<'s>(
-    iter: &mut impl Iterator<Item = XmlElement<'s>>, attrs: &str,
-  ) -> Self {
-    for TagAttribute { key, value: _ } in TagAttributeIterator::new(attrs) {
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+    attrs: &str,
+  ) -> Result<Self, RegistryError> {
+    for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
       match key {
         "comment" => (),
-        unknown => panic!("unknown: {:?}", unknown),
+        unknown => state.unknown_attribute(
+          "registry/commands/command",
+          unknown,
+          value,
+        )?,
       }
     }
     let mut command = GlCommand::default();
     loop {
-      match iter.next().unwrap() {
+      match next_in(iter, "command")? {
         EndTag { name: "command" } => break,
         StartTag { name: "proto", attrs } => {
           if !attrs.is_empty() {
@@ -1159,30 +5259,38 @@ impl GlCommand {
             {
               match key {
                 "group" => command.proto_group = Some(String::from(value)),
-                unknown => panic!("unknown proto attr: {:?}", unknown),
+                unknown => state.unknown_attribute(
+                  "registry/commands/command/proto",
+                  unknown,
+                  value,
+                )?,
               }
             }
           }
           loop {
-            match iter.next().unwrap() {
+            match next_in(iter, "proto")? {
               EndTag { name: "proto" } => break,
               Text(t) => command.proto.push_str(t),
               StartTag { name: "name", attrs: "" } => {
-                let n = grab_out_name_text(iter);
+                let n = grab_out_name_text(iter)?;
                 command.name.push_str(n);
                 command.proto.push_str(n);
               }
               StartTag { name: "ptype", attrs: "" } => {
-                let n = grab_out_ptype_text(iter);
+                let n = grab_out_ptype_text(iter)?;
                 command.proto.push_str(n);
               }
-              unknown => panic!("unknown: {:?}", unknown),
+              unknown => state.unexpected(
+                iter,
+                "registry/commands/command/proto",
+                unknown,
+              )?,
             }
           }
         }
-        StartTag { name: "param", attrs } => {
-          command.params.push(GlCommandParam::from_iter_and_attrs(iter, attrs))
-        }
+        StartTag { name: "param", attrs } => command
+          .params
+          .push(GlCommandParam::from_iter_and_attrs(state, iter, attrs)?),
         EmptyTag { name: "glx", attrs } => {
           command.glx_attrs = Some(String::from(attrs));
         }
@@ -1190,7 +5298,11 @@ impl GlCommand {
           for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
             match key {
               "name" => command.alias_of = Some(String::from(value)),
-              unknown => panic!("unknown: {:?}", unknown),
+              unknown => state.unknown_attribute(
+                "registry/commands/command/alias",
+                unknown,
+                value,
+              )?,
             }
           }
         }
@@ -1198,14 +5310,20 @@ impl GlCommand {
           for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
             match key {
               "name" => command.vec_equivalent = Some(String::from(value)),
-              unknown => panic!("unknown: {:?}", unknown),
+              unknown => state.unknown_attribute(
+                "registry/commands/command/vecequiv",
+                unknown,
+                value,
+              )?,
             }
           }
         }
-        unknown => panic!("unknown command content:{:?}", unknown),
+        unknown => {
+          state.unexpected(iter, "registry/commands/command", unknown)?
+        }
       }
     }
-    command
+    Ok(command)
   }
 }
 
@@ -1236,7 +5354,8 @@ fn c_type_to_rust_type(text: &str) -> String {
 struct GlobalGlCommand<'a> {
   gl_command: &'a GlCommand,
   api: ApiGroup,
-  major_version_number: i32,
+  major_version_number: u32,
+  typed_groups: &'a BTreeMap<&'a str, &'static str>,
 }
 impl core::fmt::Display for GlobalGlCommand<'_> {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -1244,7 +5363,7 @@ impl core::fmt::Display for GlobalGlCommand<'_> {
       name,
       rust_return_type,
       arg_name_and_type_list,
-      arg_name_list,
+      call_arg_list,
       trace_fmt,
       trace_args,
       docs,
@@ -1252,11 +5371,13 @@ impl core::fmt::Display for GlobalGlCommand<'_> {
       error_check,
       arity,
       extensions,
+      load_names,
     } = InfoForGlCommandPrinting::from_command_and_api(
       self.gl_command,
       self.api,
       self.major_version_number,
       false,
+      self.typed_groups,
     );
     //
     write!(
@@ -1269,17 +5390,19 @@ impl core::fmt::Display for GlobalGlCommand<'_> {
   {{
     trace!(\"calling {name}({trace_fmt});\", {trace_args});
   }}
-  let out = call_atomic_ptr_{arity}arg(\"{name}\", &{atomic_ptr_name}, {arg_name_list});
+  #[cfg(feature = \"lazy_loader\")]
+  lazy_load_if_null(\"{name}\", &{atomic_ptr_name});
+  let out = call_atomic_ptr_{arity}arg(\"{name}\", &{atomic_ptr_name}, {call_arg_list});
   {error_check}
   out
 }}
-{extensions}static {atomic_ptr_name}: APcv = ap_null();
+{extensions}pub(crate) static {atomic_ptr_name}: APcv = ap_null();
 /// Tries to load [`{name}`], returns if a non-null pointer was obtained.
 #[doc(hidden)]
 {extensions}pub unsafe fn {name}_load_with_dyn(
   get_proc_address: &mut dyn FnMut(*const c_char) -> *mut c_void
 ) -> bool {{
-  load_dyn_name_atomic_ptr(get_proc_address, b\"{name}\\0\", &{atomic_ptr_name})
+  load_dyn_name_atomic_ptr(get_proc_address, {load_names}, &{atomic_ptr_name}).0 == LoadOutcome::Loaded
 }}
 /// Checks if the pointer for [`{name}`] is loaded (non-null).
 #[inline]
@@ -1291,13 +5414,14 @@ impl core::fmt::Display for GlobalGlCommand<'_> {
       arg_name_and_type_list = arg_name_and_type_list,
       rust_return_type = rust_return_type,
       atomic_ptr_name = atomic_ptr_name,
-      arg_name_list = arg_name_list,
+      call_arg_list = call_arg_list,
       docs = docs,
       trace_fmt = trace_fmt,
       trace_args = trace_args,
       error_check = error_check,
       arity = arity,
       extensions = extensions,
+      load_names = load_names,
     )
   }
 }
@@ -1305,7 +5429,11 @@ impl core::fmt::Display for GlobalGlCommand<'_> {
 struct StructLoaderDisplayer<'a> {
   gl_commands: &'a [GlCommand],
   api: ApiGroup,
-  major_version_number: i32,
+  major_version_number: u32,
+  typed_groups: &'a BTreeMap<&'a str, &'static str>,
+  /// If a command's methods are written in some other `impl GlFns` block.
+  /// The struct still gets a field for it.
+  methods_elsewhere: &'a dyn Fn(&GlCommand) -> bool,
 }
 impl core::fmt::Display for StructLoaderDisplayer<'_> {
   fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -1319,17 +5447,29 @@ impl core::fmt::Display for StructLoaderDisplayer<'_> {
       f,
       "  impl GlFns {{
     /// Constructs a new struct with all pointers loaded by the `get_proc_address` given.
+    ///
+    /// Use [`load_with_report`](Self::load_with_report) to also learn which functions loaded and which didn't.
     pub unsafe fn load_with<F>(
-      mut get_proc_address: F,
+      get_proc_address: F,
     ) -> Self
+    where
+      F: FnMut(*const c_char) -> *mut c_void
+    {{
+      Self::load_with_report(get_proc_address).0
+    }}
+
+    /// Like [`load_with`](Self::load_with), but also gives a [`LoadReport`] of which functions loaded.
+    pub unsafe fn load_with_report<F>(
+      mut get_proc_address: F,
+    ) -> (Self, LoadReport)
     where
       F: FnMut(*const c_char) -> *mut c_void
     {{
       // Safety: The `GlFns` struct is nothing but `AtomicPtr` fields,
       // which can be safely constructed with `zeroed`.
       let out: Self = core::mem::zeroed();
-      out.load_all_with_dyn(&mut get_proc_address);
-      out
+      let report = out.load_all_with_dyn(&mut get_proc_address);
+      (out, report)
     }}
 
     #[cfg(feature = \"debug_automatic_glGetError\")]#[inline(never)]
@@ -1347,100 +5487,70 @@ impl core::fmt::Display for StructLoaderDisplayer<'_> {
     pub unsafe fn load_all_with_dyn(
       &self,
       get_proc_address: &mut dyn FnMut(*const c_char) -> *mut c_void,
-    ) {{"
+    ) -> LoadReport {{
+      let mut report = LoadReport::new();"
     );
-    for gl_command in self.gl_commands.iter() {
-      if gl_command.extensions.is_none() {
-        show!(
-          f,
-          "  self.{short_name}_load_with_dyn(get_proc_address);",
-          short_name = &gl_command.name[2..]
-        );
+    for (i, gl_command) in self.gl_commands.iter().enumerate() {
+      let InfoForGlCommandPrinting {
+        extensions,
+        atomic_ptr_name,
+        load_names,
+        ..
+      } = InfoForGlCommandPrinting::from_command_and_api(
+        gl_command,
+        self.api,
+        self.major_version_number,
+        true,
+        self.typed_groups,
+      );
+      let load = format!(
+        "report.record({i}, load_dyn_name_atomic_ptr(get_proc_address, {load_names}, &self.{atomic_ptr_name}));",
+        i = i,
+        load_names = load_names,
+        atomic_ptr_name = atomic_ptr_name,
+      );
+      if gl_command.extensions.is_none() && gl_command.apis.is_none() {
+        show!(f, "  {}", load);
       } else {
-        let InfoForGlCommandPrinting { extensions, .. } =
-          InfoForGlCommandPrinting::from_command_and_api(
-            gl_command,
-            self.api,
-            self.major_version_number,
-            true,
-          );
         show!(
           f,
           "  {extensions}
           {{
-            self.{short_name}_load_with_dyn(get_proc_address);
+            {load}
           }}",
-          short_name = &gl_command.name[2..],
+          load = load,
           extensions = extensions,
         );
       }
     }
+    show!(f, "  report");
     show!(f, "  }}");
     for gl_command in self.gl_commands.iter() {
-      let InfoForGlCommandPrinting {
-        name,
-        rust_return_type,
-        arg_name_and_type_list,
-        arg_name_list,
-        docs,
-        atomic_ptr_name,
-        trace_fmt,
-        trace_args,
-        error_check,
-        arity,
-        extensions,
-      } = InfoForGlCommandPrinting::from_command_and_api(
-        gl_command,
-        self.api,
-        self.major_version_number,
-        true,
-      );
-      let short_name = &name[2..];
-      //
+      let InfoForGlCommandPrinting { atomic_ptr_name, extensions, .. } =
+        InfoForGlCommandPrinting::from_command_and_api(
+          gl_command,
+          self.api,
+          self.major_version_number,
+          true,
+          self.typed_groups,
+        );
       struct_fields.push(format!(
         "{extensions}{atomic_ptr_name}: APcv",
         atomic_ptr_name = atomic_ptr_name,
         extensions = extensions,
       ));
-      show!(
-        f,
-        "{docs}
-  #[cfg_attr(feature=\"inline\", inline)]
-  #[cfg_attr(feature=\"inline_always\", inline(always))]
-  {extensions}pub unsafe fn {short_name}(&self, {arg_name_and_type_list}){rust_return_type} {{
-    #[cfg(all(debug_assertions, feature = \"debug_trace_calls\"))]
-    {{
-      trace!(\"calling gl.{short_name}({trace_fmt});\", {trace_args});
-    }}
-    let out = call_atomic_ptr_{arity}arg(\"{name}\", &self.{atomic_ptr_name}, {arg_name_list});
-    {error_check}
-    out
-  }}
-  {extensions}#[doc(hidden)]
-  pub unsafe fn {short_name}_load_with_dyn(
-    &self,
-    get_proc_address: &mut dyn FnMut(*const c_char) -> *mut c_void
-  ) -> bool {{
-    load_dyn_name_atomic_ptr(get_proc_address, b\"{name}\\0\", &self.{atomic_ptr_name})
-  }}
-  #[inline]
-  #[doc(hidden)]
-  {extensions}pub fn {short_name}_is_loaded(&self) -> bool {{
-    !self.{atomic_ptr_name}.load(RELAX).is_null()
-  }}",
-        name = name,
-        short_name = short_name,
-        arg_name_and_type_list = arg_name_and_type_list,
-        rust_return_type = rust_return_type,
-        docs = docs,
-        atomic_ptr_name = atomic_ptr_name,
-        arg_name_list = arg_name_list,
-        trace_fmt = trace_fmt,
-        trace_args = trace_args,
-        error_check = error_check,
-        arity = arity,
-        extensions = extensions,
-      );
+      if !(self.methods_elsewhere)(gl_command) {
+        show!(
+          f,
+          "{}",
+          GlFnsMethods {
+            gl_command,
+            api: self.api,
+            major_version_number: self.major_version_number,
+            typed_groups: self.typed_groups,
+          }
+        );
+      }
     }
     show!(
       f,
@@ -1465,11 +5575,86 @@ impl core::fmt::Display for StructLoaderDisplayer<'_> {
   }
 }
 
+/// The methods of [`GlFns`] for one command.
+struct GlFnsMethods<'a> {
+  gl_command: &'a GlCommand,
+  api: ApiGroup,
+  major_version_number: u32,
+  typed_groups: &'a BTreeMap<&'a str, &'static str>,
+}
+impl core::fmt::Display for GlFnsMethods<'_> {
+  fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    let InfoForGlCommandPrinting {
+      name,
+      rust_return_type,
+      arg_name_and_type_list,
+      call_arg_list,
+      docs,
+      atomic_ptr_name,
+      trace_fmt,
+      trace_args,
+      error_check,
+      arity,
+      extensions,
+      load_names,
+    } = InfoForGlCommandPrinting::from_command_and_api(
+      self.gl_command,
+      self.api,
+      self.major_version_number,
+      true,
+      self.typed_groups,
+    );
+    let short_name = &name[2..];
+    write!(
+      f,
+      "{docs}
+#[cfg_attr(feature=\"inline\", inline)]
+#[cfg_attr(feature=\"inline_always\", inline(always))]
+{extensions}pub unsafe fn {short_name}(&self, {arg_name_and_type_list}){rust_return_type} {{
+  #[cfg(all(debug_assertions, feature = \"debug_trace_calls\"))]
+  {{
+    trace!(\"calling gl.{short_name}({trace_fmt});\", {trace_args});
+  }}
+  let out = call_atomic_ptr_{arity}arg(\"{name}\", &self.{atomic_ptr_name}, {call_arg_list});
+  {error_check}
+  out
+}}
+{extensions}#[doc(hidden)]
+pub unsafe fn {short_name}_load_with_dyn(
+  &self,
+  get_proc_address: &mut dyn FnMut(*const c_char) -> *mut c_void
+) -> bool {{
+  load_dyn_name_atomic_ptr(get_proc_address, {load_names}, &self.{atomic_ptr_name}).0 == LoadOutcome::Loaded
+}}
+#[inline]
+#[doc(hidden)]
+{extensions}pub fn {short_name}_is_loaded(&self) -> bool {{
+  !self.{atomic_ptr_name}.load(RELAX).is_null()
+}}",
+      name = name,
+      short_name = short_name,
+      arg_name_and_type_list = arg_name_and_type_list,
+      rust_return_type = rust_return_type,
+      docs = docs,
+      atomic_ptr_name = atomic_ptr_name,
+      call_arg_list = call_arg_list,
+      trace_fmt = trace_fmt,
+      trace_args = trace_args,
+      error_check = error_check,
+      arity = arity,
+      extensions = extensions,
+      load_names = load_names,
+    )
+  }
+}
+
 struct InfoForGlCommandPrinting {
   name: String,
   atomic_ptr_name: String,
   arg_name_and_type_list: String,
-  arg_name_list: String,
+  /// The args as they're passed to the function pointer, which is different
+  /// from just the arg names when an arg has a group type.
+  call_arg_list: String,
   rust_return_type: String,
   docs: String,
   trace_fmt: String,
@@ -1477,11 +5662,13 @@ struct InfoForGlCommandPrinting {
   error_check: String,
   arity: usize,
   extensions: String,
+  /// The names to load the command by, as a slice of byte strings.
+  load_names: String,
 }
 impl InfoForGlCommandPrinting {
   fn from_command_and_api(
-    gl_command: &GlCommand, api: ApiGroup, major_version_number: i32,
-    struct_mode: bool,
+    gl_command: &GlCommand, api: ApiGroup, major_version_number: u32,
+    struct_mode: bool, typed_groups: &BTreeMap<&str, &'static str>,
   ) -> Self {
     let name = gl_command.name.clone();
     let atomic_ptr_name = format!("{name}_p", name = name);
@@ -1496,27 +5683,28 @@ impl InfoForGlCommandPrinting {
     };
     let mut arg_name_and_type_list = String::new();
     let mut arg_name_list = String::new();
+    let mut call_arg_list = String::new();
     let mut fn_type_list = String::new();
     let mut docs_notes_list = String::new();
     let mut trace_fmt = String::new();
     let mut trace_args = String::new();
     let arity = gl_command.params.len();
     for gl_command_param in gl_command.params.iter() {
-      let mut words_iter = gl_command_param.text.split_whitespace();
-      let arg_name = {
-        let temp_name = words_iter.next_back().unwrap();
-        if temp_name == "type" {
-          "type_"
-        } else if temp_name == "ref" {
-          "ref_"
-        } else {
-          temp_name
-        }
+      let arg_name = match gl_command_param.name() {
+        "type" => "type_",
+        "ref" => "ref_",
+        name => name,
       };
-      let arg_type_text = gl_command_param.text
-        [..gl_command_param.text.len() - arg_name.len()]
-        .trim();
-      let arg_type = c_type_to_rust_type(arg_type_text);
+      let mut arg_type = c_type_to_rust_type(gl_command_param.c_type());
+      let mut call_arg = String::from(arg_name);
+      if let Some(group) = gl_command_param.group() {
+        if typed_groups.contains_key(group)
+          && (arg_type == "GLenum" || arg_type == "GLbitfield")
+        {
+          arg_type = String::from(group);
+          call_arg.push_str(".0");
+        }
+      }
       //
       if !arg_name_and_type_list.is_empty() {
         arg_name_and_type_list.push_str(", ")
@@ -1524,6 +5712,9 @@ impl InfoForGlCommandPrinting {
       if !arg_name_list.is_empty() {
         arg_name_list.push_str(", ")
       }
+      if !call_arg_list.is_empty() {
+        call_arg_list.push_str(", ")
+      }
       if !fn_type_list.is_empty() {
         fn_type_list.push_str(", ")
       }
@@ -1536,6 +5727,7 @@ impl InfoForGlCommandPrinting {
       //
       arg_name_and_type_list.push_str(arg_name);
       arg_name_list.push_str(arg_name);
+      call_arg_list.push_str(&call_arg);
       arg_name_and_type_list.push_str(": ");
       arg_name_and_type_list.push_str(&arg_type);
       fn_type_list.push_str(&arg_type);
@@ -1561,7 +5753,7 @@ impl InfoForGlCommandPrinting {
         trace_fmt.push_str("{:?}");
         trace_args.push_str("transmute::<_, Option<fn()>>(");
         trace_args.push_str(arg_name);
-        trace_args.push_str(")");
+        trace_args.push(')');
       } else {
         // otherwise just "Debug" it.
         trace_fmt.push_str("{:?}");
@@ -1682,8 +5874,8 @@ impl InfoForGlCommandPrinting {
         return name.to_string();
       }
       for suffix in SUFFIX_LIST.iter().copied() {
-        if name.ends_with(suffix) {
-          return name[..name.len() - suffix.len()].to_string();
+        if let Some(stripped) = name.strip_suffix(suffix) {
+          return stripped.to_string();
         }
       }
       name.to_string()
@@ -1736,30 +5928,25 @@ impl InfoForGlCommandPrinting {
     } else {
       String::from("")
     };
-    let mut extensions = String::from("");
-    if let Some(list) = gl_command.extensions.as_ref() {
-      extensions.push_str("#[cfg(any(");
-      for (i, l) in list.iter().enumerate() {
-        if i != 0 {
-          extensions.push(',');
-        }
-        write!(extensions, "feature=\"{l}\"", l = l).unwrap();
-      }
-      extensions.push_str("))]");
-      //
-      extensions.push_str("#[cfg_attr(docs_rs, doc(cfg(any(");
-      for (i, l) in list.iter().enumerate() {
-        if i != 0 {
-          extensions.push(',');
-        }
-        write!(extensions, "feature=\"{l}\"", l = l).unwrap();
+    let extensions = item_cfg(
+      gl_command.apis.as_ref(),
+      gl_command.extensions.as_ref(),
+      gl_command.core_apis.as_ref(),
+    );
+    let mut load_names = String::from("&[");
+    for (i, load_name) in
+      core::iter::once(&name).chain(gl_command.load_aliases.iter()).enumerate()
+    {
+      if i != 0 {
+        load_names.push_str(", ");
       }
-      extensions.push_str("))))]");
-    };
+      let _cant_fail = write!(load_names, "b\"{}\\0\"", load_name);
+    }
+    load_names.push(']');
     Self {
       name,
       arg_name_and_type_list,
-      arg_name_list,
+      call_arg_list,
       rust_return_type,
       docs,
       atomic_ptr_name,
@@ -1768,138 +5955,170 @@ impl InfoForGlCommandPrinting {
       error_check,
       arity,
       extensions,
+      load_names,
     }
   }
 }
 
+/// A named set of enums, such as the values allowed for a command parameter.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlGroup {
+  /// The group's name.
+  pub name: String,
+  /// The names of the enums within this group, sorted.
+  pub enums: Vec<String>,
+}
+
 /// An argument to a GL function.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlCommandParam {
   text: String,
+  name: String,
   group: Option<String>,
   len: Option<String>,
 }
 impl GlCommandParam {
+  /// The parameter's name, as written in `gl.xml`.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(concat!(
+  ///   "<registry><commands namespace=\"GL\"><command>",
+  ///   "<proto>void <name>glBufferData</name></proto>",
+  ///   "<param>const void *<name>data</name></param>",
+  ///   "</command></commands></registry>",
+  /// ));
+  /// let param = &registry.command("glBufferData").unwrap().params[0];
+  /// assert_eq!(param.name(), "data");
+  /// assert_eq!(param.c_type(), "const void *");
+  /// ```
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// The C type of the parameter, as written in `gl.xml`.
+  pub fn c_type(&self) -> &str {
+    self.text.strip_suffix(self.name.as_str()).unwrap_or(&self.text).trim()
+  }
+
+  /// The enum group of the parameter, if any.
+  pub fn group(&self) -> Option<&str> {
+    self.group.as_deref()
+  }
+
+  /// The `len` attribute of the parameter, if any, which says how many
+  /// elements a pointer parameter points to.
+  pub fn len(&self) -> Option<&str> {
+    self.len.as_deref()
+  }
+
   fn from_iter_and_attrs<'s>(
-    iter: &mut impl Iterator<Item = XmlElement<'s>>, attrs: &str,
-  ) -> Self {
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+    attrs: &str,
+  ) -> Result<Self, RegistryError> {
     let mut text = String::new();
+    let mut name = String::new();
     let mut group = None;
     let mut len = None;
     for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
       match key {
         "group" => group = Some(String::from(value)),
         "len" => len = Some(String::from(value)),
-        unknown => panic!("unknown: {:?}", unknown),
+        unknown => state.unknown_attribute(
+          "registry/commands/command/param",
+          unknown,
+          value,
+        )?,
       }
     }
     loop {
-      match iter.next().unwrap() {
+      match next_in(iter, "param")? {
         EndTag { name: "param" } => break,
         StartTag { name: "ptype", attrs: "" } => {
-          text.push_str(grab_out_ptype_text(iter))
+          text.push_str(grab_out_ptype_text(iter)?)
         }
         StartTag { name: "name", attrs: "" } => {
+          name = String::from(grab_out_name_text(iter)?);
           text.push(' ');
-          text.push_str(grab_out_name_text(iter))
+          text.push_str(&name)
         }
         Text(t) => text.push_str(t),
-        unknown => panic!("unknown: {:?}", unknown),
+        unknown => {
+          state.unexpected(iter, "registry/commands/command/param", unknown)?
+        }
       }
     }
-    Self { text, group, len }
+    Ok(Self { text, name, group, len })
   }
 }
 
 /// A given GL API you can target.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlFeature {
   /// What API group this feature is part of.
   pub api: ApiGroup,
   /// The name of the feature.
   pub name: String,
   /// The version number of the feature.
-  pub number: String,
+  pub number: GlVersion,
   /// New requirements compared to the previous feature in the same API group.
   pub required: Vec<GlRequirement>,
   /// Things to remove compared to the previous feature in the same API group.
   pub remove: Vec<GlRemoval>,
 }
 impl GlFeature {
+  /// Gives `None` for a feature of an unknown API in a lenient parse.
   fn from_iter_and_attrs<'s>(
-    iter: &mut impl Iterator<Item = XmlElement<'s>>, attrs: &str,
-  ) -> Self {
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+    attrs: &str,
+  ) -> Result<Option<Self>, RegistryError> {
     let mut feature = Self::default();
+    let mut known_api = true;
     for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
       match key {
-        "api" => feature.api = ApiGroup::from(value),
+        "api" => match state.api_attr("registry/feature", value)? {
+          Some(api) => feature.api = api,
+          None => known_api = false,
+        },
         "name" => feature.name.push_str(value),
-        "number" => feature.number.push_str(value),
-        unknown => panic!("unknown: {:?}", unknown),
+        "number" => {
+          feature.number = value.parse().map_err(|msg| {
+            RegistryError::at(value, "feature", Some("number"), msg)
+          })?
+        }
+        unknown => {
+          state.unknown_attribute("registry/feature", unknown, value)?
+        }
       }
     }
     loop {
-      match iter.next().unwrap() {
-        EndTag { name: "feature" } => return feature,
+      match next_in(iter, "feature")? {
+        EndTag { name: "feature" } => {
+          return Ok(Some(feature).filter(|_| known_api))
+        }
         StartTag { name: "require", attrs } => {
           let mut profile = None;
           for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
             match key {
               "comment" => (),
               "profile" => profile = Some(String::from(value)),
-              unknown => panic!("unknown: {:?}", unknown),
+              unknown => state.unknown_attribute(
+                "registry/feature/require",
+                unknown,
+                value,
+              )?,
             }
           }
-          loop {
-            match iter.next().unwrap() {
-              EndTag { name: "require" } => break,
-              EmptyTag { name: "type", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api: None,
-                      adjustment: ReqRem::Type(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "enum", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api: None,
-                      adjustment: ReqRem::Enum(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "command", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api: None,
-                      adjustment: ReqRem::Command(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              unknown => panic!("unknown: {:?}", unknown),
-            }
+          for adjustment in
+            gather_req_rem_entries(state, iter, "registry/feature/require")?
+          {
+            feature.required.push(GlRequirement {
+              profile: profile.clone(),
+              api: None,
+              adjustment,
+            });
           }
         }
         EmptyTag { name: "require", attrs: _ } => (),
@@ -1909,59 +6128,61 @@ impl GlFeature {
             match key {
               "comment" => (),
               "profile" => profile = Some(String::from(value)),
-              unknown => panic!("unknown: {:?}", unknown),
+              unknown => state.unknown_attribute(
+                "registry/feature/remove",
+                unknown,
+                value,
+              )?,
             }
           }
-          loop {
-            match iter.next().unwrap() {
-              EndTag { name: "remove" } => break,
-              EmptyTag { name: "type", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.remove.push(GlRemoval {
-                      profile: profile.clone(),
-                      adjustment: ReqRem::Type(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "enum", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.remove.push(GlRemoval {
-                      profile: profile.clone(),
-                      adjustment: ReqRem::Enum(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "command", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => feature.remove.push(GlRemoval {
-                      profile: profile.clone(),
-                      adjustment: ReqRem::Command(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              unknown => panic!("{:?}", unknown),
-            }
+          for adjustment in
+            gather_req_rem_entries(state, iter, "registry/feature/remove")?
+          {
+            feature
+              .remove
+              .push(GlRemoval { profile: profile.clone(), adjustment });
           }
         }
-        unknown => panic!("unknown 'feature' content:{:?}", unknown),
+        unknown => state.unexpected(iter, "registry/feature", unknown)?,
+      }
+    }
+  }
+}
+
+/// Gathers the `type`, `enum`, and `command` entries of a `require` or
+/// `remove` block at `path`, up to and including the closing tag.
+fn gather_req_rem_entries<'s>(
+  state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+  path: &str,
+) -> Result<Vec<ReqRem>, RegistryError> {
+  let element = last_path_part(path);
+  let mut entries = Vec::new();
+  loop {
+    let (name, attrs) = match next_in(iter, element)? {
+      EndTag { name } if name == element => return Ok(entries),
+      EmptyTag { name, attrs }
+        if ["type", "enum", "command"].contains(&name) =>
+      {
+        (name, attrs)
+      }
+      unknown => {
+        state.unexpected(iter, path, unknown)?;
+        continue;
+      }
+    };
+    for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
+      match key {
+        "name" => entries.push(match name {
+          "type" => ReqRem::Type(String::from(value)),
+          "enum" => ReqRem::Enum(String::from(value)),
+          _ => ReqRem::Command(String::from(value)),
+        }),
+        "comment" => (),
+        unknown => state.unknown_attribute(
+          &format!("{}/{}", path, name),
+          unknown,
+          value,
+        )?,
       }
     }
   }
@@ -1971,6 +6192,7 @@ impl GlFeature {
 ///
 /// These stack as you advance through the API levels.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlRequirement {
   /// Some requirements are limited to a specific profile.
   pub profile: Option<String>,
@@ -1982,6 +6204,7 @@ pub struct GlRequirement {
 
 /// Something to remove compared to the previous API level.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlRemoval {
   profile: Option<String>,
   adjustment: ReqRem,
@@ -1989,6 +6212,8 @@ pub struct GlRemoval {
 
 /// Tags a requirement or removal as being a Type / Enum / Command.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ReqRem {
   /// A required type.
   Type(String),
@@ -2000,6 +6225,7 @@ pub enum ReqRem {
 
 /// A vendor-specific API extension you might want to use.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlExtension {
   /// The extension's name.
   pub name: String,
@@ -2010,94 +6236,117 @@ pub struct GlExtension {
   pub required: Vec<GlRequirement>,
 }
 impl GlExtension {
+  /// If this extension can be used with the API group given.
+  pub fn is_supported_by(&self, api: ApiGroup) -> bool {
+    self.supported.split('|').any(|s| s == api.supported())
+  }
+
+  /// The vendor part of the name, such as `ARB` for `GL_ARB_sync`.
+  pub fn vendor(&self) -> &str {
+    let name = self.name.strip_prefix("GL_").unwrap_or(&self.name);
+    name.split('_').next().unwrap_or(name)
+  }
+
+  /// The things this extension requires when used with the API group given.
+  /// ```rust
+  /// # use phosphorus::*;
+  /// let registry = GlRegistry::from_gl_xml_str(
+  ///   r#"<registry><extensions>
+  ///   <extension name="GL_EXT_x" supported="gl|gles2">
+  ///     <require><enum name="GL_X_EXT"/></require>
+  ///     <require api="gles2"><command name="glXEXT"/></require>
+  ///   </extension>
+  ///   </extensions></registry>"#,
+  /// );
+  /// let ext = registry.extension("GL_EXT_x").unwrap();
+  /// assert_eq!(ext.vendor(), "EXT");
+  /// assert!(ext.is_supported_by(ApiGroup::Gl));
+  /// assert!(!ext.is_supported_by(ApiGroup::Gles1));
+  /// assert_eq!(ext.requirements_for(ApiGroup::Gl).count(), 1);
+  /// assert_eq!(ext.requirements_for(ApiGroup::Gles2).count(), 2);
+  /// ```
+  pub fn requirements_for(
+    &self, api: ApiGroup,
+  ) -> impl Iterator<Item = &ReqRem> + '_ {
+    self
+      .required
+      .iter()
+      .filter(move |req| req.api.map(|a| a == api).unwrap_or(true))
+      .map(|req| &req.adjustment)
+  }
+
   fn from_iter_and_attrs<'s>(
-    iter: &mut impl Iterator<Item = XmlElement<'s>>, attrs: &str,
-  ) -> Self {
+    state: &mut ParseState, iter: &mut impl Iterator<Item = XmlElement<'s>>,
+    attrs: &str,
+  ) -> Result<Self, RegistryError> {
     let mut extension = Self::default();
     for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
       match key {
         "name" => extension.name.push_str(value),
         "supported" => extension.supported.push_str(value),
         "comment" => (),
-        unknown => panic!("unknown: {:?}", unknown),
+        unknown => state.unknown_attribute(
+          "registry/extensions/extension",
+          unknown,
+          value,
+        )?,
       }
     }
     loop {
-      match iter.next().unwrap() {
-        EndTag { name: "extension" } => return extension,
+      match next_in(iter, "extension")? {
+        EndTag { name: "extension" } => return Ok(extension),
         StartTag { name: "require", attrs } => {
           let mut profile = None;
           let mut api = None;
+          let mut known_api = true;
           for TagAttribute { key, value } in TagAttributeIterator::new(attrs) {
             match key {
               "comment" => (),
               "profile" => profile = Some(String::from(value)),
-              "api" => api = Some(ApiGroup::from(value)),
-              unknown => panic!("unknown: {:?}", unknown),
+              "api" => match state
+                .api_attr("registry/extensions/extension/require", value)?
+              {
+                Some(a) => api = Some(a),
+                None => known_api = false,
+              },
+              unknown => state.unknown_attribute(
+                "registry/extensions/extension/require",
+                unknown,
+                value,
+              )?,
             }
           }
-          loop {
-            match iter.next().unwrap() {
-              EndTag { name: "require" } => break,
-              EmptyTag { name: "type", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => extension.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api,
-                      adjustment: ReqRem::Type(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "enum", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => extension.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api,
-                      adjustment: ReqRem::Enum(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              EmptyTag { name: "command", attrs } => {
-                for TagAttribute { key, value } in
-                  TagAttributeIterator::new(attrs)
-                {
-                  match key {
-                    "name" => extension.required.push(GlRequirement {
-                      profile: profile.clone(),
-                      api,
-                      adjustment: ReqRem::Command(String::from(value)),
-                    }),
-                    "comment" => (),
-                    unknown => panic!("unknown: {:?}", unknown),
-                  }
-                }
-              }
-              unknown => panic!("unknown: {:?}", unknown),
-            }
+          let adjustments = gather_req_rem_entries(
+            state,
+            iter,
+            "registry/extensions/extension/require",
+          )?;
+          if !known_api {
+            continue;
+          }
+          for adjustment in adjustments {
+            extension.required.push(GlRequirement {
+              profile: profile.clone(),
+              api,
+              adjustment,
+            });
           }
         }
-        unknown => panic!("unknown 'feature' content:{:?}", unknown),
+        unknown => {
+          state.unexpected(iter, "registry/extensions/extension", unknown)?
+        }
       }
     }
   }
 }
 
 /// The broad API groups.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ApiGroup {
   /// OpenGL
+  #[default]
   Gl,
   /// OpenGL ES 1
   Gles1,
@@ -2117,19 +6366,23 @@ impl ApiGroup {
     }
   }
 }
-impl Default for ApiGroup {
-  fn default() -> Self {
-    ApiGroup::Gl
-  }
-}
 impl From<&str> for ApiGroup {
   fn from(s: &str) -> Self {
+    match s.parse() {
+      Ok(api) => api,
+      Err(e) => panic!("{}", e),
+    }
+  }
+}
+impl core::str::FromStr for ApiGroup {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
     match s {
-      "gl" => ApiGroup::Gl,
-      "gles1" => ApiGroup::Gles1,
-      "gles2" => ApiGroup::Gles2,
-      "glsc2" => ApiGroup::Glsc2,
-      _ => panic!("illegal:{}", s),
+      "gl" => Ok(ApiGroup::Gl),
+      "gles1" => Ok(ApiGroup::Gles1),
+      "gles2" => Ok(ApiGroup::Gles2),
+      "glsc2" => Ok(ApiGroup::Glsc2),
+      _ => Err(format!("illegal api group: {}", s)),
     }
   }
 }
@@ -0,0 +1,262 @@
+//! These build the bindings for a small registry along with a program that
+//! uses them, then run that program, since that's the only way to check what
+//! the generated code actually does.
+
+use phosphorus::*;
+use std::{path::PathBuf, process::Command};
+
+/// A registry with the commands listed out of order, so that anything which
+/// relies on them being sorted gets checked.
+const REGISTRY: &str = concat!(
+  "<registry><types>",
+  "<type>typedef unsigned int <name>GLenum</name>;</type>",
+  "<type>typedef int <name>GLint</name>;</type>",
+  "<type>typedef unsigned int <name>GLuint</name>;</type>",
+  "<type>typedef unsigned char <name>GLubyte</name>;</type>",
+  "</types><commands namespace=\"GL\">",
+  "<command><proto>void <name>glPushMatrix</name></proto></command>",
+  "<command><proto>void <name>glPopMatrix</name></proto></command>",
+  "<command><proto>void <name>glFlush</name></proto></command>",
+  "<command><proto>void <name>glFinish</name></proto></command>",
+  "<command><proto>void <name>glFinishAPPLE</name></proto>",
+  "<alias name=\"glFinish\"/></command>",
+  "<command><proto>const <ptype>GLubyte</ptype> *<name>glGetString</name>",
+  "</proto><param><ptype>GLenum</ptype> <name>name</name></param></command>",
+  "<command><proto>void <name>glGetIntegerv</name></proto>",
+  "<param><ptype>GLenum</ptype> <name>pname</name></param>",
+  "<param><ptype>GLint</ptype> *<name>data</name></param></command>",
+  "</commands>",
+  "<feature api=\"gl\" name=\"GL_VERSION_1_0\" number=\"1.0\"><require>",
+  "<command name=\"glPushMatrix\"/><command name=\"glPopMatrix\"/>",
+  "<command name=\"glFlush\"/><command name=\"glFinish\"/>",
+  "</require></feature>",
+  "<feature api=\"gl\" name=\"GL_VERSION_2_0\" number=\"2.0\"><require>",
+  "<command name=\"glGetString\"/><command name=\"glGetIntegerv\"/>",
+  "</require></feature>",
+  "<feature api=\"gles2\" name=\"GL_ES_VERSION_2_0\" number=\"2.0\">",
+  "<require><command name=\"glFlush\"/></require></feature>",
+  "<extensions><extension name=\"GL_EXT_x\" supported=\"gl\"><require>",
+  "<command name=\"glGetString\"/><command name=\"glGetIntegerv\"/>",
+  "</require></extension></extensions></registry>",
+);
+
+/// Builds `bindings` as the `gl` crate with the `features` given, then builds
+/// `main_rs` against that and runs it, panicking if any step fails.
+fn run_against(name: &str, bindings: &str, features: &[&str], main_rs: &str) {
+  let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+  std::fs::create_dir_all(&dir).unwrap();
+  std::fs::write(dir.join("gl.rs"), bindings).unwrap();
+  std::fs::write(dir.join("main.rs"), main_rs).unwrap();
+  let rustc = std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+  let mut lib = Command::new(&rustc);
+  lib.current_dir(&dir).args(["--edition=2018", "--crate-type=rlib", "gl.rs"]);
+  for feature in features {
+    lib.arg("--cfg").arg(format!("feature=\"{}\"", feature));
+  }
+  check(lib);
+  let mut bin = Command::new(&rustc);
+  bin.current_dir(&dir).args([
+    "--edition=2018",
+    "--extern=gl=libgl.rlib",
+    "-o",
+    "main",
+    "main.rs",
+  ]);
+  check(bin);
+  check(Command::new(dir.join("main")));
+}
+
+fn check(mut command: Command) {
+  let output = command.output().unwrap();
+  assert!(
+    output.status.success(),
+    "{:?} failed:\n{}{}",
+    command,
+    String::from_utf8_lossy(&output.stdout),
+    String::from_utf8_lossy(&output.stderr)
+  );
+}
+
+fn select(
+  api: ApiGroup, version: GlVersion, extensions: &[&str],
+) -> GlApiSelection {
+  GlApiSelection::new_from_registry_api_extensions(
+    &GlRegistry::from_gl_xml_str(REGISTRY),
+    api,
+    version,
+    GlProfile::Core,
+    extensions,
+  )
+}
+
+#[test]
+fn load_report_outcomes_match_names() {
+  let main_rs = r#"
+use gl::*;
+use core::ffi::{c_char, c_void, CStr};
+
+fn get_proc_address(name: *const c_char) -> *mut c_void {
+  match unsafe { CStr::from_ptr(name) }.to_str().unwrap() {
+    "glPushMatrix" | "glFinishAPPLE" => 0x1000 as *mut c_void,
+    "glPopMatrix" => usize::MAX as *mut c_void,
+    _ => core::ptr::null_mut(),
+  }
+}
+
+fn check(report: &LoadReport) {
+  assert_eq!(report.outcome("glFinish"), Some(LoadOutcome::Loaded));
+  assert_eq!(report.outcome("glFlush"), Some(LoadOutcome::Missing));
+  assert_eq!(report.outcome("glPopMatrix"), Some(LoadOutcome::Rejected));
+  assert_eq!(report.outcome("glPushMatrix"), Some(LoadOutcome::Loaded));
+  assert_eq!(report.outcome("glFinishAPPLE"), None);
+  assert_eq!(report.loaded().collect::<Vec<_>>(), ["glFinish", "glPushMatrix"]);
+  assert_eq!(report.missing().collect::<Vec<_>>(), ["glFlush"]);
+  assert_eq!(report.rejected().collect::<Vec<_>>(), ["glPopMatrix"]);
+  assert_eq!(report.bound_name("glFinish"), Some("glFinishAPPLE"));
+  assert_eq!(report.bound_name("glPushMatrix"), Some("glPushMatrix"));
+  assert_eq!(report.aliased().collect::<Vec<_>>(), [("glFinish", "glFinishAPPLE")]);
+  assert!(!report.is_complete());
+}
+
+fn main() {
+  check(&unsafe { load_global_gl_with(get_proc_address) });
+  assert!(glFinish_is_loaded());
+  assert!(!glFlush_is_loaded());
+  assert!(!glPopMatrix_is_loaded());
+  let (fns, report) = unsafe { GlFns::load_with_report(get_proc_address) };
+  check(&report);
+  assert!(fns.Finish_is_loaded());
+  assert!(!fns.PopMatrix_is_loaded());
+}
+"#;
+  run_against(
+    "load_report_outcomes_match_names",
+    &select(ApiGroup::Gl, GlVersion::new(1, 0), &[]).to_string(),
+    &["global_loader", "struct_loader"],
+    main_rs,
+  );
+}
+
+#[test]
+fn context_info_parses_version_strings() {
+  let main_rs = r#"
+use gl::*;
+use core::ffi::{c_char, c_void, CStr};
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+static VERSION: AtomicPtr<u8> = AtomicPtr::new(core::ptr::null_mut());
+
+extern "system" fn get_string(name: GLenum) -> *const GLubyte {
+  match name {
+    0x1F02 => VERSION.load(Ordering::Relaxed),
+    _ => b"\0".as_ptr(),
+  }
+}
+
+// Leaves the version integers at zero, so only the string is used.
+extern "system" fn get_integerv(_pname: GLenum, _data: *mut GLint) {}
+
+fn get_proc_address(name: *const c_char) -> *mut c_void {
+  match unsafe { CStr::from_ptr(name) }.to_bytes() {
+    b"glGetString" => get_string as *mut c_void,
+    b"glGetIntegerv" => get_integerv as *mut c_void,
+    _ => core::ptr::null_mut(),
+  }
+}
+
+fn main() {
+  unsafe { load_global_gl_with(get_proc_address) };
+  for (version, expected) in [
+    (&b"4.6.0 NVIDIA 535\0"[..], ((4, 6), false)),
+    (b"OpenGL ES 3.2 Mesa\0", ((3, 2), true)),
+    (b"OpenGL ES-CM 1.1\0", ((1, 1), true)),
+    (b"\0", ((0, 0), false)),
+  ] {
+    VERSION.store(version.as_ptr() as *mut u8, Ordering::Relaxed);
+    let info = unsafe { query_global_context_info() };
+    assert_eq!((info.version, info.is_es), expected, "{:?}", version);
+  }
+}
+"#;
+  run_against(
+    "context_info_parses_version_strings",
+    &select(ApiGroup::Gl, GlVersion::new(2, 0), &[]).to_string(),
+    &["global_loader"],
+    main_rs,
+  );
+}
+
+#[test]
+fn context_info_needs_ungated_queries() {
+  // the queries are there either way, but `ContextInfo` can only use them if
+  // they're always there.
+  let has_context_info = |selection: &GlApiSelection| {
+    let bindings = selection.to_string();
+    assert!(bindings.contains("pub unsafe fn glGetString("));
+    bindings.contains("pub struct ContextInfo")
+  };
+  let gl_2_0 = select(ApiGroup::Gl, GlVersion::new(2, 0), &[]);
+  assert!(has_context_info(&gl_2_0));
+  // the queries come from an extension
+  let ext = select(ApiGroup::Gl, GlVersion::new(1, 0), &["GL_EXT_x"]);
+  assert!(!has_context_info(&ext));
+  // the queries are only in one of the APIs
+  let gles_2_0 = select(ApiGroup::Gles2, GlVersion::new(2, 0), &[]);
+  assert!(!has_context_info(&GlApiSelection::union(&gl_2_0, &gles_2_0)));
+}
+
+#[test]
+fn lazy_loader_loads_on_first_call() {
+  let main_rs = r#"
+use gl::*;
+use core::ffi::{c_char, c_void, CStr};
+use std::{panic::catch_unwind, sync::atomic::{AtomicUsize, Ordering}};
+
+static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+extern "system" fn nothing() {}
+
+fn get_proc_address(name: *const c_char) -> *mut c_void {
+  LOOKUPS.fetch_add(1, Ordering::Relaxed);
+  match unsafe { CStr::from_ptr(name) }.to_bytes() {
+    b"glPushMatrix" | b"glFinishAPPLE" => nothing as *mut c_void,
+    _ => core::ptr::null_mut(),
+  }
+}
+
+fn main() {
+  std::panic::set_hook(Box::new(|_| ()));
+  assert!(catch_unwind(|| unsafe { glPushMatrix() }).is_err());
+  set_loader(&get_proc_address);
+  assert!(!glPushMatrix_is_loaded());
+  unsafe { glPushMatrix() };
+  assert!(glPushMatrix_is_loaded());
+  let lookups = LOOKUPS.load(Ordering::Relaxed);
+  unsafe { glPushMatrix() };
+  assert_eq!(LOOKUPS.load(Ordering::Relaxed), lookups);
+  // only the alias is found.
+  unsafe { glFinish() };
+  assert!(glFinish_is_loaded());
+  // nothing is found, so the call panics.
+  assert!(catch_unwind(|| unsafe { glFlush() }).is_err());
+  assert!(!glFlush_is_loaded());
+  // a closure can be stored, and replaces the first loader.
+  let flush = nothing as usize;
+  set_loader(Box::leak(Box::new(move |name: *const c_char| {
+    match unsafe { CStr::from_ptr(name) }.to_bytes() {
+      b"glFlush" => flush as *mut c_void,
+      _ => core::ptr::null_mut(),
+    }
+  })));
+  unsafe { glFlush() };
+  assert!(glFlush_is_loaded());
+  assert!(glPushMatrix_is_loaded());
+}
+"#;
+  run_against(
+    "lazy_loader_loads_on_first_call",
+    &select(ApiGroup::Gl, GlVersion::new(1, 0), &[]).to_string(),
+    &["global_loader", "lazy_loader"],
+    main_rs,
+  );
+}